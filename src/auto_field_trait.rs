@@ -1,6 +1,10 @@
+use crate::config::SoftDeleteStrategy;
+use crate::extract_hook::{get_default_query_hook, DefaultQueryHook};
 use async_trait::async_trait;
-use sea_orm:: Select;
+use sea_orm::sea_query::SimpleExpr;
+use sea_orm::Select;
 use std::fmt::Debug;
+use std::sync::{Arc, LazyLock};
 
 /// 上下文信息提供者 Trait (开发者需要实现此接口)
 pub trait ContextInfoProvider: Send + Sync + Debug {
@@ -18,11 +22,26 @@ pub trait ContextInfoProvider: Send + Sync + Debug {
 
     /// 获取当前租户名称
     fn get_current_tenant_name(&self) -> Option<String>;
+
+    /// 获取当前用户额外可见的租户ID集合（如集团管理员可见的子公司），默认没有
+    fn get_current_tenant_ids(&self) -> Option<Vec<String>> {
+        None
+    }
+
+    /// 获取当前用户所属部门ID，默认没有
+    fn get_current_dept_id(&self) -> Option<String> {
+        None
+    }
+
+    /// 获取当前用户的数据权限范围，默认为全部数据
+    fn get_current_data_scope(&self) -> DataScope {
+        DataScope::All
+    }
 }
 
 /// 查询扩展 Trait (用于查询数据库记录)
 pub trait QueryExtensions: sea_orm::EntityTrait {
-    /// 查询未删除的记录
+    /// 查询未删除的记录，可使用 [`not_deleted_condition`] 按表的软删除配置生成条件
     fn find_not_deleted() -> Select<Self>;
 
     /// 按租户ID查询 (查找属于某个租户的记录)
@@ -38,12 +57,12 @@ pub trait QueryExtensions: sea_orm::EntityTrait {
 /// 自定义扩展 Trait
 #[async_trait]
 pub trait CustomizationExt: sea_orm::EntityTrait {
-    /// 软删除单个记录
+    /// 软删除单个记录，可使用 [`soft_delete_column`]、[`deleted_value_expr`] 按表的软删除配置生成删除标记
     async fn soft_delete<C>(db: &C, id: &str) -> Result<(), sea_orm::DbErr>
     where
         C: sea_orm::ConnectionTrait;
 
-    /// 软删除多个记录
+    /// 软删除多个记录，可使用 [`soft_delete_column`]、[`deleted_value_expr`] 按表的软删除配置生成删除标记
     async fn soft_delete_many<C>(db: &C, ids: &[String]) -> Result<(), sea_orm::DbErr>
     where
         C: sea_orm::ConnectionTrait;
@@ -57,6 +76,53 @@ pub trait CustomizationExt: sea_orm::EntityTrait {
         I: IntoIterator<Item = Self::ActiveModel>;
 }
 
+/// 获取全局默认查询钩子，未注册时使用默认过滤字段配置的钩子
+fn effective_query_hook() -> Arc<DefaultQueryHook> {
+    static FALLBACK: LazyLock<Arc<DefaultQueryHook>> = LazyLock::new(|| Arc::new(DefaultQueryHook::new(false, false)));
+    get_default_query_hook().unwrap_or_else(|| FALLBACK.clone())
+}
+
+/// 获取表生效的软删除策略（按表覆盖的配置优先于全局配置）
+pub fn soft_delete_strategy(table_name: &str) -> SoftDeleteStrategy {
+    effective_query_hook().soft_delete_strategy(table_name)
+}
+
+/// 获取表生效的软删除字段名
+pub fn soft_delete_column(table_name: &str) -> String {
+    effective_query_hook().soft_delete_column(table_name)
+}
+
+/// 按表生效的软删除配置生成未删除条件
+///
+/// 标记字段为 `table.column = 0`，布尔字段为 `table.column = false`，删除时间字段为 `table.column IS NULL`；
+/// 字段名与取值使用全局默认查询钩子的过滤字段配置
+pub fn not_deleted_condition(table_name: &str) -> SimpleExpr {
+    effective_query_hook().not_deleted_condition(table_name)
+}
+
+/// 按表生效的软删除配置生成删除标记的值
+///
+/// 标记字段为 `1`，布尔字段为 `true`，删除时间字段为全局时钟的当前时间
+pub fn deleted_value_expr(table_name: &str) -> SimpleExpr {
+    effective_query_hook().deleted_value_expr(table_name)
+}
+
+/// 数据权限范围，在租户过滤之外按部门或创建人限制可见的行
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum DataScope {
+    /// 全部数据
+    #[default]
+    All,
+    /// 本部门：`dept_column = 当前部门`
+    Dept,
+    /// 本部门及下级部门：`dept_column IN (当前部门, 下级部门...)`，下级部门由调用方提供
+    DeptAndSub(Vec<String>),
+    /// 仅本人创建：`owner_column = 当前用户ID`
+    Own,
+    /// 自定义部门：`dept_column IN (...)`
+    Custom(Vec<String>),
+}
+
 /// 自动字段上下文结构
 #[derive(Debug, Clone, Default)]
 pub struct AutoFieldContext {
@@ -65,6 +131,12 @@ pub struct AutoFieldContext {
     pub real_name: Option<String>,
     pub tenant_id: Option<String>,
     pub tenant_name: Option<String>,
+    /// 额外可见的租户ID集合，与 `tenant_id` 一起生成 `tenant_id IN (...)` 条件
+    pub tenant_ids: Option<Vec<String>>,
+    /// 当前用户所属部门ID
+    pub dept_id: Option<String>,
+    /// 数据权限范围
+    pub data_scope: DataScope,
 }
 
 impl AutoFieldContext {
@@ -83,6 +155,47 @@ impl AutoFieldContext {
         self
     }
 
+    /// 设置额外可见的租户ID集合
+    pub fn with_tenant_ids(mut self, tenant_ids: Option<Vec<String>>) -> Self {
+        self.tenant_ids = tenant_ids;
+        self
+    }
+
+    /// 可见的租户ID：`tenant_id` 在前，其后为 `tenant_ids` 中的其余租户，忽略空值与重复值
+    pub fn visible_tenant_ids(&self) -> Vec<String> {
+        let mut visible: Vec<String> = Vec::new();
+        for tenant_id in self.tenant_id.iter().chain(self.tenant_ids.iter().flatten()) {
+            if !tenant_id.is_empty() && !visible.contains(tenant_id) {
+                visible.push(tenant_id.clone());
+            }
+        }
+        visible
+    }
+
+    /// 设置部门及数据权限范围
+    pub fn with_data_scope(mut self, dept_id: Option<String>, data_scope: DataScope) -> Self {
+        self.dept_id = dept_id;
+        self.data_scope = data_scope;
+        self
+    }
+
+    /// 数据权限范围内可见的部门ID，忽略空值与重复值；全部数据及仅本人创建时为空
+    pub fn data_scope_dept_ids(&self) -> Vec<String> {
+        let dept_ids: Vec<&String> = match &self.data_scope {
+            DataScope::All | DataScope::Own => return Vec::new(),
+            DataScope::Dept => self.dept_id.iter().collect(),
+            DataScope::DeptAndSub(sub_dept_ids) => self.dept_id.iter().chain(sub_dept_ids).collect(),
+            DataScope::Custom(dept_ids) => dept_ids.iter().collect(),
+        };
+        let mut visible: Vec<String> = Vec::new();
+        for dept_id in dept_ids {
+            if !dept_id.is_empty() && !visible.contains(dept_id) {
+                visible.push(dept_id.clone());
+            }
+        }
+        visible
+    }
+
     /// 从上下文提供者创建上下文
     pub fn from_provider(provider: &dyn ContextInfoProvider) -> Self {
         Self {
@@ -91,6 +204,9 @@ impl AutoFieldContext {
             real_name: provider.get_current_real_name(),
             tenant_id: provider.get_current_tenant_id(),
             tenant_name: provider.get_current_tenant_name(),
+            tenant_ids: provider.get_current_tenant_ids(),
+            dept_id: provider.get_current_dept_id(),
+            data_scope: provider.get_current_data_scope(),
         }
     }
 
@@ -0,0 +1,37 @@
+use sea_orm::prelude::{DateTime, DateTimeLocal};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// 时钟 Trait，为自动填充的时间字段提供当前时间（开发者可替换实现，如统一使用 UTC 或测试中固定时间）
+pub trait Clock: Send + Sync {
+    /// 获取当前时间
+    fn now(&self) -> DateTime;
+}
+
+/// 默认时钟，使用系统本地时间
+#[derive(Debug, Clone, Default)]
+pub struct LocalClock;
+
+impl Clock for LocalClock {
+    fn now(&self) -> DateTime {
+        DateTimeLocal::from(SystemTime::now()).naive_local()
+    }
+}
+
+/// 全局时钟注册表，未注册时使用 LocalClock
+static CLOCK_REGISTRY: parking_lot::RwLock<Option<Arc<dyn Clock>>> = parking_lot::RwLock::new(None);
+
+/// 注册全局时钟
+pub fn register_clock(clock: Arc<dyn Clock>) {
+    let mut registry = CLOCK_REGISTRY.write();
+    *registry = Some(clock);
+}
+
+/// 获取全局时钟的当前时间
+pub fn current_time() -> DateTime {
+    let registry = CLOCK_REGISTRY.read();
+    match registry.as_ref() {
+        Some(clock) => clock.now(),
+        None => LocalClock.now(),
+    }
+}
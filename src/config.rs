@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use schemars::JsonSchema;
 use serde::Deserialize;
 use spring::config::Configurable;
@@ -45,8 +45,160 @@ pub struct SeaOrmConfig {
     pub enable_soft_delete: Option<bool>,
     /// 是否开启多租户
     #[serde(default = "default_bool")]
-    pub enable_tenant_filter: Option<bool>
+    pub enable_tenant_filter: Option<bool>,
+    /// 是否启用严格租户模式，开启租户过滤且上下文缺少租户时拒绝执行语句
+    #[serde(default = "default_bool")]
+    pub enable_strict_tenant: Option<bool>,
+    /// SQL 解析失败时的处理策略，默认允许执行并记录警告
+    pub on_parse_failure: Option<ParseFailurePolicy>,
+    /// 租户层级配置，配置后上级租户可见下级租户的数据
+    pub tenant_hierarchy: Option<TenantHierarchyConfig>,
+    /// 是否按上下文中的数据权限范围添加部门、创建人过滤，只对配置了对应字段的表生效
+    #[serde(default = "default_bool")]
+    pub enable_data_scope: Option<bool>,
+    /// 是否对 UPDATE 语句添加租户与软删除过滤
+    #[serde(default = "default_bool")]
+    pub enable_update_filter: Option<bool>,
+    /// 是否对 DELETE 语句添加租户与软删除过滤
+    #[serde(default = "default_bool")]
+    pub enable_delete_filter: Option<bool>,
+    /// 是否将 DELETE 语句改写为软删除（跳过表及 `hard_delete` 作用域内仍为物理删除）
+    #[serde(default = "default_bool")]
+    pub enable_soft_delete_rewrite: Option<bool>,
+    /// 是否在 INSERT 语句中自动填充创建时间、创建人及租户字段
+    #[serde(default = "default_bool")]
+    pub enable_insert_fill: Option<bool>,
+    /// 是否在 UPDATE 语句中自动维护更新时间、更新人及版本号
+    #[serde(default = "default_bool")]
+    pub enable_update_fill: Option<bool>,
+    /// 包含版本号字段的表，UPDATE 时自动递增版本号
+    pub version_table: Option<HashSet<String>>,
+    /// 是否在插件启动时从数据库读取表结构，只对拥有对应字段的表添加过滤条件及填充字段
+    #[serde(default = "default_bool")]
+    pub enable_schema_introspection: Option<bool>,
+    /// 慢查询阈值（毫秒），配置后记录执行耗时超过阈值的语句及其租户、用户
+    pub slow_query_threshold_ms: Option<u64>,
+    /// SQL 改写结果缓存的容量，默认 1024，为 0 时不缓存
+    pub rewrite_cache_size: Option<usize>,
+    /// 全局的过滤字段配置，未配置的项使用 delete_flag = 0、tenant_id = 当前租户ID
+    pub filter_columns: Option<FilterColumnConfig>,
+    /// 按表覆盖的过滤字段配置，未配置的项使用全局配置
+    pub table_filter_columns: Option<HashMap<String, FilterColumnConfig>>
+
+}
+
+/// 过滤字段配置
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize)]
+pub struct FilterColumnConfig {
+    /// 软删除策略，默认为标记字段
+    pub soft_delete_strategy: Option<SoftDeleteStrategy>,
+    /// 软删除字段名，默认 `delete_flag`
+    pub soft_delete_column: Option<String>,
+    /// 表示未删除的值，默认 `0`（布尔策略默认 `false`），时间戳策略下不使用
+    pub not_deleted_value: Option<FilterValue>,
+    /// 表示已删除的值，DELETE 改写为软删除时使用，默认 `1`（布尔策略默认 `true`），时间戳策略下不使用
+    pub deleted_value: Option<FilterValue>,
+    /// 租户字段名，默认 `tenant_id`
+    pub tenant_column: Option<String>,
+    /// 租户字段的取值来源，默认取上下文中的租户ID
+    pub tenant_value_source: Option<TenantValueSource>,
+    /// 所有租户共享的行的租户字段取值（如 `'0'`），查询时以 OR 分支包含这些行
+    pub shared_tenant_values: Option<Vec<FilterValue>>,
+    /// 租户字段为 NULL 的行是否为所有租户共享的行
+    pub shared_tenant_null: Option<bool>,
+    /// 数据权限的部门字段名，未配置的表不按部门过滤
+    pub dept_column: Option<String>,
+    /// 数据权限的创建人字段名（如 `create_id`），未配置的表不按创建人过滤
+    pub owner_column: Option<String>,
+}
+
+impl FilterColumnConfig {
+    /// 合并配置，本配置中未设置的项使用 `fallback` 中的值
+    pub fn or(&self, fallback: &FilterColumnConfig) -> FilterColumnConfig {
+        FilterColumnConfig {
+            soft_delete_strategy: self.soft_delete_strategy.or(fallback.soft_delete_strategy),
+            soft_delete_column: self.soft_delete_column.clone().or_else(|| fallback.soft_delete_column.clone()),
+            not_deleted_value: self.not_deleted_value.clone().or_else(|| fallback.not_deleted_value.clone()),
+            deleted_value: self.deleted_value.clone().or_else(|| fallback.deleted_value.clone()),
+            tenant_column: self.tenant_column.clone().or_else(|| fallback.tenant_column.clone()),
+            tenant_value_source: self.tenant_value_source.or(fallback.tenant_value_source),
+            shared_tenant_values: self.shared_tenant_values.clone().or_else(|| fallback.shared_tenant_values.clone()),
+            shared_tenant_null: self.shared_tenant_null.or(fallback.shared_tenant_null),
+            dept_column: self.dept_column.clone().or_else(|| fallback.dept_column.clone()),
+            owner_column: self.owner_column.clone().or_else(|| fallback.owner_column.clone()),
+        }
+    }
+}
+
+/// 租户层级配置
+#[derive(Debug, Clone, JsonSchema, Deserialize)]
+pub struct TenantHierarchyConfig {
+    /// 下级租户的查询方式，默认为缓存的闭包表
+    #[serde(default)]
+    pub mode: TenantHierarchyMode,
+    /// 闭包表或租户表的表名
+    pub table: String,
+    /// 下级租户ID字段：闭包表为下级字段，租户表为租户ID字段
+    pub id_column: String,
+    /// 上级租户ID字段：闭包表为上级字段，租户表为上级租户字段
+    pub parent_column: String,
+}
+
+/// 下级租户的查询方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, JsonSchema, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TenantHierarchyMode {
+    /// 启动时读取闭包表并缓存，以 IN 列表注入
+    #[default]
+    ClosureTable,
+    /// 以递归 CTE 子查询注入，不缓存
+    RecursiveCte,
+}
+
+/// 过滤字段的取值，支持数字、布尔及字符串
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize)]
+#[serde(untagged)]
+pub enum FilterValue {
+    Bool(bool),
+    Number(i64),
+    Text(String),
+}
+
+/// 软删除策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, JsonSchema, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SoftDeleteStrategy {
+    /// 标记字段，如 `delete_flag = 0`，也可配置为字符标记 `'N'`/`'Y'`
+    #[default]
+    Flag,
+    /// 布尔字段，如 `is_deleted = false`
+    Boolean,
+    /// 删除时间字段，如 `deleted_at IS NULL`，删除时写入当前时间
+    Timestamp,
+}
+
+/// SQL 解析失败时的处理策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, JsonSchema, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParseFailurePolicy {
+    /// 按原语句执行，记录警告日志
+    #[default]
+    Allow,
+    /// 按原语句执行，并记录审计日志
+    AllowAndAudit,
+    /// 拒绝执行，返回 `HookError::ParseFailure`；无法完成改写的语句同样拒绝执行
+    Reject,
+}
 
+/// 租户字段的取值来源
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, JsonSchema, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TenantValueSource {
+    /// 上下文中的租户ID
+    #[default]
+    TenantId,
+    /// 上下文中的租户名称
+    TenantName,
 }
 
 fn default_bool() -> Option<bool> {
@@ -0,0 +1,81 @@
+//! 查询钩子的错误类型
+//!
+//! 钩子只能通过 `DbErr` 返回错误，这里以错误码作为 `DbErr::Custom` 消息的前缀，
+//! 调用方可通过 `HookErrorKind::of` 识别被钩子拒绝的语句
+
+use sea_orm::DbErr;
+use thiserror::Error;
+
+/// 查询钩子拒绝执行语句的错误
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum HookError {
+    /// 严格租户模式下上下文中没有租户ID
+    #[error("[MISSING_TENANT] tenant filter requires a tenant in context, table: {table}")]
+    MissingTenant { table: String },
+
+    /// 解析失败策略为拒绝时，无法解析的 SQL
+    #[error("[PARSE_FAILURE] SQL could not be parsed for filtering ({backend}): {message}")]
+    ParseFailure { backend: String, message: String },
+
+    /// 外连接中可为空一侧无法添加过滤条件（如 USING、NATURAL 连接的多表一侧）
+    #[error("[UNFILTERABLE_JOIN] outer join side cannot be filtered without changing the join: {relation}")]
+    UnfilterableJoin { relation: String },
+
+    /// 以同步方式执行的钩子链中包含只能异步执行的钩子
+    #[error("[ASYNC_HOOK] async hook cannot run in a synchronous hook chain, priority: {priority}")]
+    AsyncHook { priority: i32 },
+}
+
+/// 查询钩子错误的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookErrorKind {
+    MissingTenant,
+    ParseFailure,
+    UnfilterableJoin,
+    AsyncHook,
+}
+
+impl HookError {
+    /// 错误类型
+    pub fn kind(&self) -> HookErrorKind {
+        match self {
+            HookError::MissingTenant { .. } => HookErrorKind::MissingTenant,
+            HookError::ParseFailure { .. } => HookErrorKind::ParseFailure,
+            HookError::UnfilterableJoin { .. } => HookErrorKind::UnfilterableJoin,
+            HookError::AsyncHook { .. } => HookErrorKind::AsyncHook,
+        }
+    }
+}
+
+impl HookErrorKind {
+    /// 错误码，作为错误消息的前缀
+    pub fn code(self) -> &'static str {
+        match self {
+            HookErrorKind::MissingTenant => "[MISSING_TENANT]",
+            HookErrorKind::ParseFailure => "[PARSE_FAILURE]",
+            HookErrorKind::UnfilterableJoin => "[UNFILTERABLE_JOIN]",
+            HookErrorKind::AsyncHook => "[ASYNC_HOOK]",
+        }
+    }
+
+    /// 识别由查询钩子返回的错误，其他错误返回 None
+    pub fn of(err: &DbErr) -> Option<Self> {
+        let DbErr::Custom(message) = err else {
+            return None;
+        };
+        [
+            HookErrorKind::MissingTenant,
+            HookErrorKind::ParseFailure,
+            HookErrorKind::UnfilterableJoin,
+            HookErrorKind::AsyncHook,
+        ]
+        .into_iter()
+        .find(|kind| message.starts_with(kind.code()))
+    }
+}
+
+impl From<HookError> for DbErr {
+    fn from(err: HookError) -> Self {
+        DbErr::Custom(err.to_string())
+    }
+}
@@ -1,17 +1,216 @@
+use crate::auto_field_trait::{AutoFieldContext, DataScope};
+use crate::config::{FilterColumnConfig, FilterValue, ParseFailurePolicy, SoftDeleteStrategy, TenantValueSource};
+use crate::error::{HookError, HookErrorKind};
+use crate::filter_scope::{FilterKind, is_filter_disabled, is_system_scope, scope_disabled_filters};
+use crate::rewrite_cache::{
+    ContextValue, DEFAULT_REWRITE_CACHE_SIZE, RewriteCache, RewriteCacheKey, RewriteCacheStats, RewriteTemplate, TemplateValue,
+};
+use crate::schema_registry::SchemaRegistry;
+use crate::tenant_hierarchy::TenantHierarchyProvider;
 use parking_lot::RwLock;
-use sea_orm::{ConnectionTrait, DatabaseBackend, DbErr, ExecResult, QueryResult, Statement};
-use sqlparser::dialect::GenericDialect;
+use sea_orm::prelude::DateTime;
+use sea_orm::sea_query::{Alias, Expr, SimpleExpr};
+use sea_orm::{
+    AccessMode, ConnectionTrait, DatabaseBackend, DatabaseTransaction, DbErr, ExecResult, IsolationLevel, QueryResult,
+    Statement, StreamTrait, TransactionError, TransactionTrait,
+};
+use sqlparser::dialect::{Dialect, GenericDialect, MySqlDialect, PostgreSqlDialect, SQLiteDialect};
 use sqlparser::parser::Parser;
-use std::collections::HashSet;
+use sqlparser::tokenizer::{Location, Token, Tokenizer};
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
 
 /// 查询钩子 Trait，用于拦截和修改 SQL 查询
 pub trait QueryHook: Send + Sync {
     /// 在执行查询前调用，可以修改 SQL 语句
-    fn before_query(&self, sql: &str) -> Result<Option<String>, DbErr>;
+    ///
+    /// `backend` 为当前连接的数据库类型，用于选择对应的 SQL 方言
+    fn before_query(&self, sql: &str, backend: DatabaseBackend) -> Result<Option<String>, DbErr>;
 
-    /// 在执行查询后调用
-    fn after_query(&self, sql: &str, result: &Result<(), &DbErr>);
+    /// 在执行带绑定参数的语句前调用，可以修改语句
+    ///
+    /// 默认实现将参数内联后交给 `before_query` 处理，改写后的语句不再携带绑定参数；
+    /// 需要保留绑定参数的钩子应覆盖此方法
+    fn before_statement(&self, stmt: &Statement) -> Result<Option<Statement>, DbErr> {
+        Ok(self
+            .before_query(&stmt.to_string(), stmt.db_backend)?
+            .map(|sql| Statement::from_string(stmt.db_backend, sql)))
+    }
+
+    /// 在执行查询后调用，`outcome` 包含执行的语句、耗时及影响行数等信息
+    fn after_query(&self, outcome: &QueryOutcome);
+}
+
+/// 语句类型，按最终执行的 SQL 的语法树判断
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatementKind {
+    Select,
+    Insert,
+    Update,
+    Delete,
+    Other,
+}
+
+impl StatementKind {
+    /// 按数据库方言解析 SQL 并根据首条语句判断类型，`WITH ... UPDATE` 等语句取 WITH 之后的部分
+    ///
+    /// 无法解析或不是查询、增删改的语句（如 `SHOW`）按首个关键字判断
+    pub fn of(sql: &str, backend: DatabaseBackend) -> Self {
+        let dialect = dialect_for_backend(backend);
+        Parser::parse_sql(dialect.as_ref(), sql)
+            .ok()
+            .and_then(|statements| statements.first().map(Self::from_statement))
+            .filter(|kind| *kind != StatementKind::Other)
+            .unwrap_or_else(|| Self::of_keyword(sql))
+    }
+
+    /// 根据语法树判断语句类型
+    pub fn from_statement(statement: &sqlparser::ast::Statement) -> Self {
+        match statement {
+            sqlparser::ast::Statement::Query(query) => match query.body.as_ref() {
+                sqlparser::ast::SetExpr::Insert(_) => StatementKind::Insert,
+                sqlparser::ast::SetExpr::Update(_) => StatementKind::Update,
+                sqlparser::ast::SetExpr::Delete(_) => StatementKind::Delete,
+                _ => StatementKind::Select,
+            },
+            sqlparser::ast::Statement::Insert(_) => StatementKind::Insert,
+            sqlparser::ast::Statement::Update(_) => StatementKind::Update,
+            sqlparser::ast::Statement::Delete(_) => StatementKind::Delete,
+            _ => StatementKind::Other,
+        }
+    }
+
+    /// 根据 SQL 的首个关键字判断语句类型，跳过开头的空白、注释及括号
+    fn of_keyword(sql: &str) -> Self {
+        let mut rest = sql;
+        loop {
+            rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '(');
+            if let Some(comment) = rest.strip_prefix("--") {
+                rest = comment.split_once('\n').map_or("", |(_, line)| line);
+            } else if let Some(comment) = rest.strip_prefix("/*") {
+                rest = comment.split_once("*/").map_or("", |(_, tail)| tail);
+            } else {
+                break;
+            }
+        }
+        let keyword: String = rest.chars().take_while(|c| c.is_ascii_alphabetic()).collect();
+        match keyword.to_ascii_uppercase().as_str() {
+            "SELECT" | "WITH" | "VALUES" | "SHOW" => StatementKind::Select,
+            "INSERT" | "REPLACE" => StatementKind::Insert,
+            "UPDATE" => StatementKind::Update,
+            "DELETE" => StatementKind::Delete,
+            _ => StatementKind::Other,
+        }
+    }
+}
+
+/// 查询执行结果，供 `after_query` 构建慢查询日志、指标及审计
+#[derive(Debug)]
+pub struct QueryOutcome<'a> {
+    /// 调用方提交的原始 SQL
+    pub original_sql: &'a str,
+
+    /// 钩子改写后的 SQL，未改写时为 None
+    pub rewritten_sql: Option<&'a str>,
+
+    /// 执行耗时，不含钩子改写的时间
+    pub elapsed: Duration,
+
+    /// 影响的行数，仅 execute 类调用有值
+    pub rows_affected: Option<u64>,
+
+    /// 返回的行数，仅查询类调用有值
+    pub rows_returned: Option<usize>,
+
+    /// 数据库类型
+    pub backend: DatabaseBackend,
+
+    /// 执行时的上下文信息
+    pub context: AutoFieldContext,
+
+    /// 执行结果
+    pub result: Result<(), &'a DbErr>,
+}
+
+impl QueryOutcome<'_> {
+    /// 最终执行的 SQL
+    pub fn final_sql(&self) -> &str {
+        self.rewritten_sql.unwrap_or(self.original_sql)
+    }
+
+    /// 是否执行成功
+    pub fn is_ok(&self) -> bool {
+        self.result.is_ok()
+    }
+
+    /// 最终执行的语句类型，调用时才解析 SQL，不需要语句类型的钩子没有额外开销
+    pub fn kind(&self) -> StatementKind {
+        StatementKind::of(self.final_sql(), self.backend)
+    }
+}
+
+/// 异步查询钩子 Trait，可在改写 SQL 前查询数据库或缓存
+///
+/// `HookedConnection` 的异步方法会等待钩子执行完成，同步钩子可通过 `SyncHookAdapter` 适配
+#[async_trait::async_trait]
+pub trait AsyncQueryHook: Send + Sync {
+    /// 在执行查询前调用，可以修改 SQL 语句
+    async fn before_query(&self, sql: &str, backend: DatabaseBackend) -> Result<Option<String>, DbErr>;
+
+    /// 在执行带绑定参数的语句前调用，可以修改语句
+    ///
+    /// 默认实现将参数内联后交给 `before_query` 处理，改写后的语句不再携带绑定参数
+    async fn before_statement(&self, stmt: &Statement) -> Result<Option<Statement>, DbErr> {
+        Ok(self
+            .before_query(&stmt.to_string(), stmt.db_backend)
+            .await?
+            .map(|sql| Statement::from_string(stmt.db_backend, sql)))
+    }
+
+    /// 在执行查询后调用，`outcome` 包含执行的语句、耗时及影响行数等信息
+    async fn after_query(&self, outcome: &QueryOutcome<'_>);
+}
+
+/// 将同步查询钩子适配为异步查询钩子
+#[derive(Clone)]
+pub struct SyncHookAdapter {
+    hook: Arc<dyn QueryHook>,
+}
+
+impl SyncHookAdapter {
+    /// 包装同步查询钩子
+    pub fn new(hook: Arc<dyn QueryHook>) -> Self {
+        Self { hook }
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncQueryHook for SyncHookAdapter {
+    async fn before_query(&self, sql: &str, backend: DatabaseBackend) -> Result<Option<String>, DbErr> {
+        self.hook.before_query(sql, backend)
+    }
+
+    async fn before_statement(&self, stmt: &Statement) -> Result<Option<Statement>, DbErr> {
+        self.hook.before_statement(stmt)
+    }
+
+    async fn after_query(&self, outcome: &QueryOutcome<'_>) {
+        self.hook.after_query(outcome)
+    }
+}
+
+/// 根据数据库类型获取对应的 SQL 方言
+pub fn dialect_for_backend(backend: DatabaseBackend) -> Box<dyn Dialect> {
+    match backend {
+        DatabaseBackend::MySql => Box::new(MySqlDialect {}),
+        DatabaseBackend::Postgres => Box::new(PostgreSqlDialect {}),
+        DatabaseBackend::Sqlite => Box::new(SQLiteDialect {}),
+    }
 }
 
 /// 默认查询钩子实现
@@ -23,8 +222,187 @@ pub struct DefaultQueryHook {
     /// 是否启用租户过滤
     pub enable_tenant_filter: bool,
 
+    /// 是否对 UPDATE 语句添加默认过滤条件
+    pub enable_update_filter: bool,
+
+    /// 是否对 DELETE 语句添加默认过滤条件
+    pub enable_delete_filter: bool,
+
+    /// 是否将 DELETE 语句改写为软删除 UPDATE
+    pub enable_soft_delete_rewrite: bool,
+
+    /// 是否在 INSERT 语句中自动填充审计与租户字段
+    pub enable_insert_fill: bool,
+
+    /// 是否在 UPDATE 语句中自动维护更新时间、更新人及版本号
+    pub enable_update_fill: bool,
+
+    /// 是否启用严格租户模式，上下文缺少租户时拒绝需要租户过滤的语句
+    pub enable_strict_tenant: bool,
+
+    /// 是否按上下文中的数据权限范围添加部门、创建人过滤
+    pub enable_data_scope: bool,
+
+    /// SQL 解析失败时的处理策略
+    pub on_parse_failure: ParseFailurePolicy,
+
     /// 需要跳过默认过滤的表名集合
     skip_tables: Arc<RwLock<HashSet<String>>>,
+
+    /// 包含版本号字段的表名集合，UPDATE 时自动递增版本号
+    version_tables: Arc<RwLock<HashSet<String>>>,
+
+    /// 全局的过滤字段配置
+    filter_columns: Arc<RwLock<FilterColumnConfig>>,
+
+    /// 按表覆盖的过滤字段配置
+    table_filter_columns: Arc<RwLock<HashMap<String, FilterColumnConfig>>>,
+
+    /// 表结构注册表，只对拥有对应字段的表添加过滤条件及填充字段
+    schema_registry: SchemaRegistry,
+
+    /// 带绑定参数语句的改写结果缓存
+    rewrite_cache: Arc<RewriteCache>,
+
+    /// 表级配置的版本，每次变更递增并计入缓存键
+    config_generation: Arc<AtomicU64>,
+
+    /// SQL 解析失败的次数
+    parse_failures: Arc<AtomicU64>,
+
+    /// 租户层级提供者，配置后上级租户可见下级租户的数据
+    tenant_hierarchy: Option<Arc<dyn TenantHierarchyProvider>>,
+}
+
+/// 单张表生效的过滤字段
+struct FilterColumns {
+    /// 软删除策略
+    soft_delete_strategy: SoftDeleteStrategy,
+
+    /// 软删除字段名
+    soft_delete_column: String,
+
+    /// 表示未删除的值
+    not_deleted_value: FilterValue,
+
+    /// 表示已删除的值
+    deleted_value: FilterValue,
+
+    /// 租户字段名
+    tenant_column: String,
+
+    /// 租户字段的取值来源
+    tenant_value_source: TenantValueSource,
+
+    /// 共享行的租户字段取值
+    shared_tenant_values: Vec<FilterValue>,
+
+    /// 租户字段为 NULL 的行是否为共享行
+    shared_tenant_null: bool,
+
+    /// 数据权限的部门字段名
+    dept_column: Option<String>,
+
+    /// 数据权限的创建人字段名
+    owner_column: Option<String>,
+}
+
+impl FilterColumns {
+    /// 根据配置生成，未配置的项使用默认值
+    fn from_config(config: FilterColumnConfig) -> Self {
+        let soft_delete_strategy = config.soft_delete_strategy.unwrap_or_default();
+        let (not_deleted_value, deleted_value) = match soft_delete_strategy {
+            SoftDeleteStrategy::Boolean => (FilterValue::Bool(false), FilterValue::Bool(true)),
+            _ => (FilterValue::Number(0), FilterValue::Number(1)),
+        };
+        Self {
+            soft_delete_strategy,
+            soft_delete_column: config.soft_delete_column.unwrap_or_else(|| "delete_flag".to_string()),
+            not_deleted_value: config.not_deleted_value.unwrap_or(not_deleted_value),
+            deleted_value: config.deleted_value.unwrap_or(deleted_value),
+            tenant_column: config.tenant_column.unwrap_or_else(|| "tenant_id".to_string()),
+            tenant_value_source: config.tenant_value_source.unwrap_or_default(),
+            shared_tenant_values: config.shared_tenant_values.unwrap_or_default(),
+            shared_tenant_null: config.shared_tenant_null.unwrap_or(false),
+            dept_column: config.dept_column,
+            owner_column: config.owner_column,
+        }
+    }
+
+    /// 从上下文中获取租户字段的值
+    fn tenant_value(&self, context: &AutoFieldContext) -> Option<String> {
+        match self.tenant_value_source {
+            TenantValueSource::TenantId => context.tenant_id.clone(),
+            TenantValueSource::TenantName => context.tenant_name.clone(),
+        }
+    }
+
+    /// 租户字段的值在上下文中的来源
+    fn tenant_source(&self) -> ContextValue {
+        match self.tenant_value_source {
+            TenantValueSource::TenantId => ContextValue::TenantId,
+            TenantValueSource::TenantName => ContextValue::TenantName,
+        }
+    }
+
+    /// 查询时可见的租户字段取值及其来源，租户ID来源包含上下文中所有可见的租户
+    fn visible_tenant_values(&self, context: &AutoFieldContext) -> Vec<(ContextValue, String)> {
+        match self.tenant_value_source {
+            TenantValueSource::TenantId => context
+                .visible_tenant_ids()
+                .into_iter()
+                .enumerate()
+                .map(|(index, value)| (ContextValue::VisibleTenant(index), value))
+                .collect(),
+            TenantValueSource::TenantName => context
+                .tenant_name
+                .clone()
+                .filter(|value| !value.is_empty())
+                .map(|value| vec![(ContextValue::TenantName, value)])
+                .unwrap_or_default(),
+        }
+    }
+
+    /// 是否配置了共享行
+    fn has_shared_tenant(&self) -> bool {
+        self.shared_tenant_null || !self.shared_tenant_values.is_empty()
+    }
+
+    /// 生成以表名限定的未删除条件
+    fn not_deleted_condition(&self, table_name: &str) -> SimpleExpr {
+        let column = Expr::col((Alias::new(table_name), Alias::new(self.soft_delete_column.as_str())));
+        match self.soft_delete_strategy {
+            SoftDeleteStrategy::Timestamp => column.is_null(),
+            _ => column.eq(filter_value(&self.not_deleted_value)),
+        }
+    }
+
+    /// 生成删除标记的值，时间戳策略为全局时钟的当前时间
+    fn deleted_value_expr(&self) -> SimpleExpr {
+        match self.soft_delete_strategy {
+            SoftDeleteStrategy::Timestamp => Expr::value(crate::clock::current_time()),
+            _ => Expr::value(filter_value(&self.deleted_value)),
+        }
+    }
+}
+
+/// 将过滤字段取值转换为绑定值
+fn filter_value(value: &FilterValue) -> sea_orm::Value {
+    match value {
+        FilterValue::Bool(value) => (*value).into(),
+        FilterValue::Number(value) => (*value).into(),
+        FilterValue::Text(value) => value.clone().into(),
+    }
+}
+
+/// 创建过滤字段取值的字面量表达式
+fn filter_value_expr(value: &FilterValue) -> sqlparser::ast::Expr {
+    let value = match value {
+        FilterValue::Bool(value) => sqlparser::ast::Value::Boolean(*value),
+        FilterValue::Number(value) => sqlparser::ast::Value::Number(value.to_string(), false),
+        FilterValue::Text(value) => sqlparser::ast::Value::SingleQuotedString(value.clone()),
+    };
+    sqlparser::ast::Expr::Value(value.with_empty_span())
 }
 
 impl DefaultQueryHook {
@@ -33,296 +411,1903 @@ impl DefaultQueryHook {
         Self {
             enable_soft_delete,
             enable_tenant_filter,
+            enable_update_filter: false,
+            enable_delete_filter: false,
+            enable_soft_delete_rewrite: false,
+            enable_insert_fill: false,
+            enable_update_fill: false,
+            enable_strict_tenant: false,
+            enable_data_scope: false,
+            on_parse_failure: ParseFailurePolicy::default(),
             skip_tables: Arc::new(RwLock::new(HashSet::new())),
+            version_tables: Arc::new(RwLock::new(HashSet::new())),
+            filter_columns: Arc::new(RwLock::new(FilterColumnConfig::default())),
+            table_filter_columns: Arc::new(RwLock::new(HashMap::new())),
+            schema_registry: SchemaRegistry::new(),
+            rewrite_cache: Arc::new(RewriteCache::new(DEFAULT_REWRITE_CACHE_SIZE)),
+            config_generation: Arc::new(AtomicU64::new(0)),
+            parse_failures: Arc::new(AtomicU64::new(0)),
+            tenant_hierarchy: None,
         }
     }
 
+    /// 设置是否对 UPDATE 语句添加默认过滤条件
+    pub fn with_update_filter(mut self, enable_update_filter: bool) -> Self {
+        self.enable_update_filter = enable_update_filter;
+        self
+    }
+
+    /// 设置是否对 DELETE 语句添加默认过滤条件
+    pub fn with_delete_filter(mut self, enable_delete_filter: bool) -> Self {
+        self.enable_delete_filter = enable_delete_filter;
+        self
+    }
+
+    /// 设置是否将 DELETE 语句改写为软删除 UPDATE
+    pub fn with_soft_delete_rewrite(mut self, enable_soft_delete_rewrite: bool) -> Self {
+        self.enable_soft_delete_rewrite = enable_soft_delete_rewrite;
+        self
+    }
+
+    /// 设置是否在 INSERT 语句中自动填充审计与租户字段
+    pub fn with_insert_fill(mut self, enable_insert_fill: bool) -> Self {
+        self.enable_insert_fill = enable_insert_fill;
+        self
+    }
+
+    /// 设置是否在 UPDATE 语句中自动维护更新时间、更新人及版本号
+    pub fn with_update_fill(mut self, enable_update_fill: bool) -> Self {
+        self.enable_update_fill = enable_update_fill;
+        self
+    }
+
+    /// 设置是否启用严格租户模式
+    ///
+    /// 开启后上下文没有租户ID（或为空）时，需要添加租户条件的语句返回 `HookError::MissingTenant`；
+    /// 跳过表、`without_filters(FilterKind::Tenant, ..)` 及 `system_scope` 作用域内的语句不受影响
+    pub fn with_strict_tenant(mut self, enable_strict_tenant: bool) -> Self {
+        self.enable_strict_tenant = enable_strict_tenant;
+        self
+    }
+
+    /// 设置是否按数据权限范围过滤
+    ///
+    /// 只对配置了 `dept_column`、`owner_column` 的表生效；`without_filters(FilterKind::DataScope, ..)` 作用域内不添加
+    pub fn with_data_scope(mut self, enable_data_scope: bool) -> Self {
+        self.enable_data_scope = enable_data_scope;
+        self
+    }
+
+    /// 设置 SQL 解析失败时的处理策略
+    pub fn with_parse_failure_policy(mut self, on_parse_failure: ParseFailurePolicy) -> Self {
+        self.on_parse_failure = on_parse_failure;
+        self
+    }
+
+    /// 获取 SQL 解析失败的次数
+    pub fn parse_failure_count(&self) -> u64 {
+        self.parse_failures.load(Ordering::Relaxed)
+    }
+
+    /// 设置改写结果缓存的容量，为 0 时不缓存
+    pub fn with_rewrite_cache_size(mut self, size: usize) -> Self {
+        self.rewrite_cache = Arc::new(RewriteCache::new(size));
+        self
+    }
+
+    /// 设置租户层级提供者，租户条件包含当前租户的全部下级租户
+    pub fn with_tenant_hierarchy(mut self, provider: Arc<dyn TenantHierarchyProvider>) -> Self {
+        self.tenant_hierarchy = Some(provider);
+        self
+    }
+
+    /// 获取改写结果缓存的统计信息
+    pub fn rewrite_cache_stats(&self) -> RewriteCacheStats {
+        self.rewrite_cache.stats()
+    }
+
     /// 添加需要跳过默认过滤的表名
     pub fn add_skip_table(&self, table_name: &str) {
         let mut skip_tables = self.skip_tables.write();
         skip_tables.insert(table_name.to_lowercase());
+        self.config_changed();
     }
 
     /// 移除需要跳过默认过滤的表名
     pub fn remove_skip_table(&self, table_name: &str) {
         let mut skip_tables = self.skip_tables.write();
         skip_tables.remove(&table_name.to_lowercase());
+        self.config_changed();
+    }
+
+    /// 表级配置变更后递增配置版本并清空缓存
+    ///
+    /// 在持有配置写锁时调用：并发改写中读到旧配置的语句以旧版本写入缓存，之后的查找不会再命中
+    fn config_changed(&self) {
+        self.config_generation.fetch_add(1, Ordering::Relaxed);
+        self.rewrite_cache.clear();
+    }
+
+    /// 检查表是否需要跳过默认过滤
+    fn should_skip_table(&self, table_name: &str) -> bool {
+        let skip_tables = self.skip_tables.read();
+        skip_tables.contains(&table_name.to_lowercase())
+    }
+
+    /// 添加包含版本号字段的表名
+    pub fn add_version_table(&self, table_name: &str) {
+        let mut version_tables = self.version_tables.write();
+        version_tables.insert(table_name.to_lowercase());
+        self.config_changed();
+    }
+
+    /// 移除包含版本号字段的表名
+    pub fn remove_version_table(&self, table_name: &str) {
+        let mut version_tables = self.version_tables.write();
+        version_tables.remove(&table_name.to_lowercase());
+        self.config_changed();
+    }
+
+    /// 检查表是否包含版本号字段
+    fn is_version_table(&self, table_name: &str) -> bool {
+        let version_tables = self.version_tables.read();
+        version_tables.contains(&table_name.to_lowercase())
+    }
+
+    /// 设置全局的过滤字段配置
+    pub fn set_filter_columns(&self, config: FilterColumnConfig) {
+        let mut filter_columns = self.filter_columns.write();
+        *filter_columns = config;
+        self.config_changed();
+    }
+
+    /// 设置指定表的过滤字段配置，未配置的项使用全局配置
+    pub fn set_table_filter_columns(&self, table_name: &str, config: FilterColumnConfig) {
+        let mut table_filter_columns = self.table_filter_columns.write();
+        table_filter_columns.insert(table_name.to_lowercase(), config);
+        self.config_changed();
+    }
+
+    /// 移除指定表的过滤字段配置
+    pub fn remove_table_filter_columns(&self, table_name: &str) {
+        let mut table_filter_columns = self.table_filter_columns.write();
+        table_filter_columns.remove(&table_name.to_lowercase());
+        self.config_changed();
+    }
+
+    /// 获取表结构注册表，可登记实体或从数据库读取表结构
+    pub fn schema_registry(&self) -> &SchemaRegistry {
+        &self.schema_registry
+    }
+
+    /// 获取表生效的软删除策略
+    pub fn soft_delete_strategy(&self, table_name: &str) -> SoftDeleteStrategy {
+        self.filter_columns_of(table_name).soft_delete_strategy
+    }
+
+    /// 获取表生效的软删除字段名
+    pub fn soft_delete_column(&self, table_name: &str) -> String {
+        self.filter_columns_of(table_name).soft_delete_column
+    }
+
+    /// 按表生效的软删除配置生成未删除条件，与查询时添加的条件一致
+    pub fn not_deleted_condition(&self, table_name: &str) -> SimpleExpr {
+        self.filter_columns_of(table_name).not_deleted_condition(table_name)
+    }
+
+    /// 按表生效的软删除配置生成删除标记的值，与 DELETE 改写为软删除时写入的值一致
+    pub fn deleted_value_expr(&self, table_name: &str) -> SimpleExpr {
+        self.filter_columns_of(table_name).deleted_value_expr()
+    }
+
+    /// 获取表生效的过滤字段
+    fn filter_columns_of(&self, table_name: &str) -> FilterColumns {
+        let filter_columns = self.filter_columns.read();
+        let table_filter_columns = self.table_filter_columns.read();
+        let config = match table_filter_columns.get(&table_name.to_lowercase()) {
+            Some(table_config) => table_config.or(&filter_columns),
+            None => filter_columns.clone(),
+        };
+        FilterColumns::from_config(config)
+    }
+
+    /// 改写带绑定参数的语句，保留原有参数，新增的值以占位符形式追加
+    ///
+    /// 改写结果以模板形式缓存，命中时只需用当前上下文生成新增参数
+    fn add_default_conditions_to_statement(&self, stmt: &Statement) -> Result<Option<Statement>, DbErr> {
+        let auto_context = self.current_context();
+        let original_count = stmt.values.as_ref().map_or(0, |values| values.0.len());
+        let template = if self.rewrite_cache.is_enabled() {
+            let key = RewriteCacheKey::new(
+                &stmt.sql,
+                stmt.db_backend,
+                self.cache_config_digest(),
+                self.config_generation.load(Ordering::Relaxed),
+                &auto_context,
+            );
+            match self.rewrite_cache.get(&key) {
+                Some(template) => template,
+                None => {
+                    let binder = ParamBinder::bound(stmt.db_backend, original_count);
+                    let template = self.build_statement_template(stmt, &binder, &auto_context)?.map(Arc::new);
+                    // 解析失败的语句不缓存，保证每次执行都按解析失败策略计数及审计
+                    if !binder.parse_failed.get() {
+                        self.rewrite_cache.insert(key, template.clone());
+                    }
+                    template
+                }
+            }
+        } else {
+            let binder = ParamBinder::bound(stmt.db_backend, original_count);
+            self.build_statement_template(stmt, &binder, &auto_context)?.map(Arc::new)
+        };
+        template.map(|template| template.instantiate(stmt, &auto_context)).transpose()
+    }
+
+    /// 获取当前上下文，以 IN 列表注入的租户层级在这里将下级租户追加到可见租户中
+    ///
+    /// 展开后的可见租户数量计入缓存键，具体取值在生成语句时从展开后的上下文获取
+    fn current_context(&self) -> AutoFieldContext {
+        let mut context = AutoFieldContext::current_safe();
+        let Some(hierarchy) = &self.tenant_hierarchy else {
+            return context;
+        };
+        if hierarchy.descendants_subquery().is_some() {
+            return context;
+        }
+
+        let visible = context.visible_tenant_ids();
+        if visible.is_empty() {
+            return context;
+        }
+        let mut tenant_ids = visible.clone();
+        for tenant_id in &visible {
+            tenant_ids.extend(hierarchy.descendants(tenant_id));
+        }
+        context.tenant_ids = Some(tenant_ids);
+        context
+    }
+
+    /// 影响改写结果的配置摘要，作为缓存键的一部分
+    ///
+    /// 包含可直接修改的开关、表结构注册表的版本及任务级的过滤控制，表级配置的版本单独计入缓存键
+    fn cache_config_digest(&self) -> u64 {
+        let flags = [
+            self.enable_soft_delete,
+            self.enable_tenant_filter,
+            self.enable_update_filter,
+            self.enable_delete_filter,
+            self.enable_soft_delete_rewrite,
+            self.enable_insert_fill,
+            self.enable_update_fill,
+            crate::filter_scope::is_hard_delete(),
+            is_filter_disabled(FilterKind::SoftDelete),
+            is_filter_disabled(FilterKind::Tenant),
+            self.enable_strict_tenant,
+            is_system_scope(),
+            self.enable_data_scope,
+            is_filter_disabled(FilterKind::DataScope),
+        ];
+        let flags = flags.iter().enumerate().fold(0u64, |digest, (index, flag)| digest | ((*flag as u64) << index));
+        (self.schema_registry.generation() << 16) | flags
+    }
+
+    /// 改写带绑定参数的语句并生成模板，不需要改写时返回 `None`
+    fn build_statement_template(
+        &self,
+        stmt: &Statement,
+        binder: &ParamBinder,
+        auto_context: &AutoFieldContext,
+    ) -> Result<Option<RewriteTemplate>, DbErr> {
+        let backend = stmt.db_backend;
+        let dialect = dialect_for_backend(backend);
+
+        // MySQL、SQLite 使用位置占位符 `?`，先编号为 `?1`、`?2`...，改写后再按出现顺序还原
+        // 编号时无法完成词法分析的 SQL 同样无法解析，按解析失败策略处理
+        let positional = backend != DatabaseBackend::Postgres;
+        let sql = if positional {
+            let mut index = 0;
+            let numbered = replace_placeholders(&stmt.sql, dialect.as_ref(), |_| {
+                index += 1;
+                Ok(format!("?{}", index))
+            });
+            let sql = match numbered {
+                Ok(sql) => sql,
+                Err(e) => {
+                    binder.parse_failed.set(true);
+                    self.handle_parse_failure(&stmt.sql, backend, &e.to_string())?;
+                    return Ok(None);
+                }
+            };
+            // 新增的占位符从原参数数量之后编号，参数不足时会与原占位符重号
+            let original_count = stmt.values.as_ref().map_or(0, |values| values.0.len());
+            if index > original_count {
+                return Err(DbErr::Custom(format!(
+                    "Missing parameter for SQL: {}, placeholders: {}, values: {}",
+                    stmt.sql, index, original_count
+                )));
+            }
+            sql
+        } else {
+            stmt.sql.clone()
+        };
+
+        let Some(modified_sql) = self.add_default_conditions(&sql, backend, binder, auto_context)? else {
+            return Ok(None);
+        };
+        if modified_sql == sql {
+            return Ok(None);
+        }
+        let values = binder.take_values();
+        if !positional {
+            return Ok(Some(RewriteTemplate { sql: modified_sql, values }));
+        }
+
+        let mut ordered_values = Vec::with_capacity(values.len());
+        let modified_sql = replace_placeholders(&modified_sql, dialect.as_ref(), |placeholder| {
+            let value = placeholder[1..]
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| index.checked_sub(1))
+                .and_then(|index| values.get(index))
+                .ok_or_else(|| DbErr::Custom(format!("Unknown placeholder in rewritten SQL: {}", placeholder)))?;
+            ordered_values.push(*value);
+            Ok("?".to_string())
+        })?;
+        Ok(Some(RewriteTemplate { sql: modified_sql, values: ordered_values }))
+    }
+
+    /// 按解析失败策略处理无法解析的 SQL，允许执行时不修改原始 SQL
+    fn handle_parse_failure(&self, sql: &str, backend: DatabaseBackend, message: &str) -> Result<Option<String>, DbErr> {
+        self.parse_failures.fetch_add(1, Ordering::Relaxed);
+        match self.on_parse_failure {
+            ParseFailurePolicy::Allow => {
+                log::warn!("Failed to parse SQL ({:?}): {}, error: {}", backend, sql, message);
+                Ok(None)
+            }
+            ParseFailurePolicy::AllowAndAudit => {
+                let context = AutoFieldContext::current_safe();
+                log::warn!(
+                    target: "audit",
+                    "unparseable SQL executed without filters ({:?}), tenant: {}, user: {}, sql: {}, error: {}",
+                    backend,
+                    context.tenant_id.as_deref().unwrap_or("-"),
+                    context.user_name.as_deref().or(context.user_id.as_deref()).unwrap_or("-"),
+                    sql,
+                    message
+                );
+                Ok(None)
+            }
+            ParseFailurePolicy::Reject => Err(HookError::ParseFailure {
+                backend: format!("{:?}", backend),
+                message: message.to_string(),
+            }
+            .into()),
+        }
+    }
+
+    /// 处理改写过程中的错误：钩子主动拒绝的错误直接返回，其余错误在解析失败策略为拒绝时同样拒绝执行，
+    /// 否则按原语句执行并记录警告
+    fn handle_rewrite_error(&self, sql: &str, backend: DatabaseBackend, err: DbErr) -> Result<(), DbErr> {
+        if HookErrorKind::of(&err).is_some() {
+            return Err(err);
+        }
+        if self.on_parse_failure == ParseFailurePolicy::Reject {
+            return Err(HookError::ParseFailure {
+                backend: format!("{:?}", backend),
+                message: err.to_string(),
+            }
+            .into());
+        }
+        log::warn!("Failed to add default conditions to SQL: {}, error: {}", sql, err);
+        Ok(())
+    }
+
+    /// 解析 SQL 并添加默认查询条件
+    ///
+    /// 根据语法树判断语句类型，不包含需要改写的语句时返回 `None`；新增的值由 `binder` 生成字面量或占位符
+    fn add_default_conditions(
+        &self,
+        sql: &str,
+        backend: DatabaseBackend,
+        binder: &ParamBinder,
+        auto_context: &AutoFieldContext,
+    ) -> Result<Option<String>, DbErr> {
+        let dialect = dialect_for_backend(backend);
+
+        let mut statements = match Parser::parse_sql(dialect.as_ref(), sql) {
+            Ok(statements) => statements,
+            Err(e) => {
+                binder.parse_failed.set(true);
+                return self.handle_parse_failure(sql, backend, &e.to_string());
+            }
+        };
+
+        // 只处理查询语句（包括 WITH、集合运算以及括号开头的查询）以及 INSERT、UPDATE、DELETE 语句
+        if !statements.iter().any(|statement| self.should_rewrite_statement(statement)) {
+            return Ok(None);
+        }
+
+        let mut rewritten = Vec::with_capacity(statements.len());
+        for statement in &mut statements {
+            if !self.should_rewrite_statement(statement) {
+                rewritten.push(statement.to_string());
+                continue;
+            }
+
+            let context = RewriteContext::new(backend, binder, auto_context.clone());
+            // DELETE 改写为软删除后，仍按 DELETE 的配置添加过滤条件
+            let soft_deleted = {
+                let (write, cte_scope) = Self::write_statement(statement);
+                *context.cte_scope.borrow_mut() = cte_scope;
+                self.convert_delete_to_soft_delete(write, &context)
+            };
+            if soft_deleted
+                && let sqlparser::ast::Statement::Query(query) = statement
+                && let sqlparser::ast::SetExpr::Delete(write) = query.body.as_ref()
+            {
+                *query.body = sqlparser::ast::SetExpr::Update(write.clone());
+            }
+            // 遍历所有嵌套查询，每个查询在自身作用域中添加条件，是否跳过由每张表各自决定
+            QueryVisitor::new(|query, cte_scope| {
+                *context.cte_scope.borrow_mut() = cte_scope.clone();
+                self.add_conditions_to_query(query, &context)
+            })
+            .visit_statement(statement)?;
+
+            let (write, cte_scope) = Self::write_statement(statement);
+            *context.cte_scope.borrow_mut() = cte_scope;
+            match write {
+                sqlparser::ast::Statement::Update(update) => {
+                    if self.enable_update_filter || (soft_deleted && self.enable_delete_filter) {
+                        context.guarding_write.set(true);
+                        self.add_conditions_to_update(update, &context)?;
+                        context.guarding_write.set(false);
+                    }
+                    if self.enable_update_fill {
+                        self.fill_update_columns(update, &context);
+                    }
+                }
+                sqlparser::ast::Statement::Delete(delete) if self.enable_delete_filter => {
+                    context.guarding_write.set(true);
+                    self.add_conditions_to_delete(delete, &context)?;
+                    context.guarding_write.set(false);
+                }
+                sqlparser::ast::Statement::Insert(insert) if self.enable_insert_fill => self.fill_insert_columns(insert, &context),
+                _ => {}
+            }
+            rewritten.push(Self::render_statement(statement, context.update_order_by.take()));
+        }
+
+        Ok(Some(rewritten.join("; ")))
+    }
+
+    /// 生成语句的 SQL
+    ///
+    /// 语法树中的 UPDATE 不支持 ORDER BY，由 `DELETE ... ORDER BY ... LIMIT` 改写的软删除
+    /// 在末尾拼接 `ORDER BY ... LIMIT ...`（MySQL、SQLite 支持该语法）
+    fn render_statement(statement: &mut sqlparser::ast::Statement, order_by: Vec<sqlparser::ast::OrderByExpr>) -> String {
+        if order_by.is_empty() {
+            return statement.to_string();
+        }
+        let limit = match Self::write_statement(statement).0 {
+            sqlparser::ast::Statement::Update(update) => update.limit.take(),
+            _ => None,
+        };
+        let order_by = order_by.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ");
+        let mut sql = format!("{} ORDER BY {}", statement, order_by);
+        if let Some(limit) = limit {
+            sql.push_str(&format!(" LIMIT {}", limit));
+        }
+        sql
+    }
+
+    /// 获取语句中的 INSERT/UPDATE/DELETE 及其可见的 CTE，`WITH ... UPDATE` 等语句取 WITH 之后的部分
+    fn write_statement(statement: &mut sqlparser::ast::Statement) -> (&mut sqlparser::ast::Statement, CteScope) {
+        use sqlparser::ast::SetExpr;
+
+        let is_write = |body: &SetExpr| matches!(body, SetExpr::Insert(_) | SetExpr::Update(_) | SetExpr::Delete(_));
+        if !matches!(statement, sqlparser::ast::Statement::Query(query) if is_write(&query.body)) {
+            return (statement, CteScope::default());
+        }
+        let sqlparser::ast::Statement::Query(query) = statement else {
+            unreachable!("checked above");
+        };
+        let cte_scope = CteScope::of(query.with.as_ref());
+        match query.body.as_mut() {
+            SetExpr::Insert(write) | SetExpr::Update(write) | SetExpr::Delete(write) => (write, cte_scope),
+            _ => unreachable!("checked above"),
+        }
+    }
+
+    /// 判断语句是否需要添加默认条件
+    ///
+    /// 查询及增删改语句中嵌套的查询总是添加条件；目标表的过滤、软删除改写及字段填充由各自的开关控制
+    fn should_rewrite_statement(&self, statement: &sqlparser::ast::Statement) -> bool {
+        matches!(
+            statement,
+            sqlparser::ast::Statement::Query(_)
+                | sqlparser::ast::Statement::Insert(_)
+                | sqlparser::ast::Statement::Update(_)
+                | sqlparser::ast::Statement::Delete(_)
+        )
+    }
+
+    /// 向 UPDATE 语句补充更新时间、更新人，并为包含版本号的表追加 `version = version + 1`
+    ///
+    /// 调用方已显式赋值的字段保持不变；MySQL 多表 UPDATE 时使用被更新表的别名限定字段
+    fn fill_update_columns(&self, update: &mut sqlparser::ast::Update, context: &RewriteContext) {
+        let sqlparser::ast::TableFactor::Table { name, alias, .. } = &update.table.relation else {
+            return;
+        };
+        let table_name = match self.table_name_of(name) {
+            Some(table_name) if !self.should_skip_table(&table_name) && !context.is_cte(name) => table_name,
+            _ => return,
+        };
+
+        let qualifier: Vec<sqlparser::ast::Ident> = match alias {
+            Some(alias) => vec![alias.name.clone()],
+            None => name.0.iter().filter_map(|part| part.as_ident().cloned()).collect(),
+        };
+        // PostgreSQL 的 SET 目标字段不允许带表名，只有 JOIN 形式的多表 UPDATE 才限定
+        let qualify_target = !update.table.joins.is_empty();
+        let qualify_value = qualify_target || update.from.is_some();
+
+        let auto_context = &context.auto_context;
+        let mut fill_values = vec![
+            ("update_time", Some(context.binder.current_time_expr())),
+            ("update_id", auto_context.user_id.clone().map(|value| context.binder.context_expr(ContextValue::UserId, value))),
+            ("update_by", auto_context.user_name.clone().map(|value| context.binder.context_expr(ContextValue::UserName, value))),
+        ];
+        if self.is_version_table(&table_name) {
+            let version = self.create_field_expr("version", qualify_value.then_some(qualifier.as_slice()));
+            fill_values.push((
+                "version",
+                Some(sqlparser::ast::Expr::BinaryOp {
+                    left: Box::new(version),
+                    op: sqlparser::ast::BinaryOperator::Plus,
+                    right: Box::new(sqlparser::ast::Expr::Value(
+                        sqlparser::ast::Value::Number("1".to_string(), false).with_empty_span(),
+                    )),
+                }),
+            ));
+        }
+
+        for (column, value) in fill_values {
+            let Some(value) = value else {
+                continue;
+            };
+            if !self.schema_registry.has_column(&table_name, column) {
+                continue;
+            }
+            let assigned = update.assignments.iter().any(|assignment| match &assignment.target {
+                sqlparser::ast::AssignmentTarget::ColumnName(target) => target
+                    .0
+                    .last()
+                    .and_then(|part| part.as_ident())
+                    .is_some_and(|ident| ident.value.eq_ignore_ascii_case(column)),
+                _ => false,
+            });
+            if assigned {
+                continue;
+            }
+
+            let mut target = if qualify_target { qualifier.clone() } else { Vec::new() };
+            target.push(sqlparser::ast::Ident::new(column));
+            update.assignments.push(sqlparser::ast::Assignment {
+                target: sqlparser::ast::AssignmentTarget::ColumnName(sqlparser::ast::ObjectName::from(target)),
+                value,
+            });
+        }
+    }
+
+    /// 向 INSERT 语句补充缺失的审计与租户字段
+    ///
+    /// 填充 create_time（取自全局时钟）、create_id、create_by，开启租户过滤时还填充租户字段与 tenant_name；
+    /// 调用方已显式设置的字段保持不变，多行 VALUES 的每一行都会补充相同的值
+    fn fill_insert_columns(&self, insert: &mut sqlparser::ast::Insert, context: &RewriteContext) {
+        let sqlparser::ast::TableObject::TableName(name) = &insert.table else {
+            return;
+        };
+        let table_name = match self.table_name_of(name) {
+            Some(table_name) if !self.should_skip_table(&table_name) => table_name,
+            _ => return,
+        };
+        // 没有列清单的 INSERT 无法确定字段位置
+        let Some(quote_style) = insert.columns.first().map(|column| column.quote_style) else {
+            return;
+        };
+        let Some(sqlparser::ast::SetExpr::Values(values)) = insert.source.as_deref_mut().map(|source| source.body.as_mut()) else {
+            return;
+        };
+
+        let auto_context = &context.auto_context;
+        let mut fill_values = vec![
+            ("create_time", Some(context.binder.current_time_expr())),
+            ("create_id", auto_context.user_id.clone().map(|value| context.binder.context_expr(ContextValue::UserId, value))),
+            ("create_by", auto_context.user_name.clone().map(|value| context.binder.context_expr(ContextValue::UserName, value))),
+        ];
+        let filter_columns = self.filter_columns_of(&table_name);
+        if self.enable_tenant_filter {
+            fill_values.push((
+                filter_columns.tenant_column.as_str(),
+                filter_columns
+                    .tenant_value(auto_context)
+                    .map(|value| context.binder.context_expr(filter_columns.tenant_source(), value)),
+            ));
+            fill_values.push((
+                "tenant_name",
+                auto_context.tenant_name.clone().map(|value| context.binder.context_expr(ContextValue::TenantName, value)),
+            ));
+        }
+
+        for (column, value) in fill_values {
+            let Some(value) = value else {
+                continue;
+            };
+            if !self.schema_registry.has_column(&table_name, column)
+                || insert.columns.iter().any(|existing| existing.value.eq_ignore_ascii_case(column))
+            {
+                continue;
+            }
+            insert.columns.push(match quote_style {
+                Some(quote) => sqlparser::ast::Ident::with_quote(quote, column),
+                None => sqlparser::ast::Ident::new(column),
+            });
+            for row in &mut values.rows {
+                row.push(value.clone());
+            }
+        }
+    }
+
+    /// 将单表 DELETE 语句改写为软删除 UPDATE，返回是否发生改写
+    ///
+    /// `DELETE FROM t WHERE ...` 改写为
+    /// `UPDATE t SET delete_flag = 1, update_time = <当前时间>, update_by = '<当前用户>' WHERE ...`
+    /// （软删除字段与取值按表的过滤字段配置，时间戳策略写入当前时间）；
+    /// 跳过表、多表删除以及 `hard_delete` 作用域内的语句保持物理删除；
+    /// 单表删除的 `ORDER BY ... LIMIT` 保留在改写后的 UPDATE 中，只标记原本会删除的行
+    fn convert_delete_to_soft_delete(&self, statement: &mut sqlparser::ast::Statement, context: &RewriteContext) -> bool {
+        if !self.enable_soft_delete_rewrite || crate::filter_scope::is_hard_delete() {
+            return false;
+        }
+        let sqlparser::ast::Statement::Delete(delete) = statement else {
+            return false;
+        };
+        if !delete.tables.is_empty() || delete.using.is_some() {
+            return false;
+        }
+        let (sqlparser::ast::FromTable::WithFromKeyword(from) | sqlparser::ast::FromTable::WithoutKeyword(from)) =
+            &mut delete.from;
+        if from.len() != 1 || !from[0].joins.is_empty() {
+            return false;
+        }
+        let sqlparser::ast::TableFactor::Table { name, .. } = &from[0].relation else {
+            return false;
+        };
+        let table_name = match self.table_name_of(name) {
+            Some(table_name) if !self.should_skip_table(&table_name) && !context.is_cte(name) => table_name,
+            _ => return false,
+        };
+        let filter_columns = self.filter_columns_of(&table_name);
+        // 没有软删除字段的表保持物理删除
+        if !self.schema_registry.has_column(&table_name, &filter_columns.soft_delete_column) {
+            return false;
+        }
+
+        let assignment = |column: &str, value: sqlparser::ast::Expr| sqlparser::ast::Assignment {
+            target: sqlparser::ast::AssignmentTarget::ColumnName(sqlparser::ast::ObjectName::from(vec![
+                sqlparser::ast::Ident::new(column),
+            ])),
+            value,
+        };
+        let mut assignments = vec![
+            assignment(&filter_columns.soft_delete_column, match filter_columns.soft_delete_strategy {
+                SoftDeleteStrategy::Timestamp => context.binder.current_time_expr(),
+                _ => filter_value_expr(&filter_columns.deleted_value),
+            }),
+        ];
+        if self.schema_registry.has_column(&table_name, "update_time") {
+            assignments.push(assignment("update_time", context.binder.current_time_expr()));
+        }
+        if let Some(user_name) = context.auto_context.user_name.clone()
+            && self.schema_registry.has_column(&table_name, "update_by")
+        {
+            assignments.push(assignment("update_by", context.binder.context_expr(ContextValue::UserName, user_name)));
+        }
+
+        let table = from.remove(0);
+        *context.update_order_by.borrow_mut() = std::mem::take(&mut delete.order_by);
+        *statement = sqlparser::ast::Statement::Update(sqlparser::ast::Update {
+            update_token: sqlparser::ast::helpers::attached_token::AttachedToken::empty(),
+            table,
+            assignments,
+            from: None,
+            selection: delete.selection.take(),
+            returning: delete.returning.take(),
+            or: None,
+            limit: delete.limit.take(),
+        });
+        true
+    }
+
+    /// 向 UPDATE 语句的 WHERE 子句添加默认条件，被更新的表及 FROM 中的表各自按跳过列表判断
+    fn add_conditions_to_update(&self, update: &mut sqlparser::ast::Update, context: &RewriteContext) -> Result<(), DbErr> {
+        let qualify = !update.table.joins.is_empty() || update.from.is_some();
+
+        let mut conditions = Vec::new();
+        self.add_conditions_to_table_with_joins(&mut update.table, qualify, &mut conditions, context)?;
+        if let Some(
+            sqlparser::ast::UpdateTableFromKind::BeforeSet(from) | sqlparser::ast::UpdateTableFromKind::AfterSet(from),
+        ) = &mut update.from
+        {
+            for table_with_joins in from {
+                self.add_conditions_to_table_with_joins(table_with_joins, qualify, &mut conditions, context)?;
+            }
+        }
+
+        Self::add_conditions_to_selection(&mut update.selection, conditions);
+        Ok(())
+    }
+
+    /// 向 DELETE 语句的 WHERE 子句添加默认条件，FROM 及 USING 中的表各自按跳过列表判断
+    fn add_conditions_to_delete(&self, delete: &mut sqlparser::ast::Delete, context: &RewriteContext) -> Result<(), DbErr> {
+        let (sqlparser::ast::FromTable::WithFromKeyword(from) | sqlparser::ast::FromTable::WithoutKeyword(from)) =
+            &mut delete.from;
+        let qualify = from.len() > 1
+            || from.iter().any(|table| !table.joins.is_empty())
+            || delete.using.is_some();
+
+        let mut conditions = Vec::new();
+        for table_with_joins in from.iter_mut().chain(delete.using.iter_mut().flatten()) {
+            self.add_conditions_to_table_with_joins(table_with_joins, qualify, &mut conditions, context)?;
+        }
+
+        Self::add_conditions_to_selection(&mut delete.selection, conditions);
+        Ok(())
+    }
+
+    /// 向查询中添加默认条件
+    fn add_conditions_to_query(&self, query: &mut sqlparser::ast::Query, context: &RewriteContext) -> Result<(), DbErr> {
+        self.add_conditions_to_set_expr(&mut query.body, context)
+    }
+
+    /// 向SetExpr中添加默认条件，支持递归处理嵌套查询
+    fn add_conditions_to_set_expr(
+        &self,
+        set_expr: &mut sqlparser::ast::SetExpr,
+        context: &RewriteContext,
+    ) -> Result<(), DbErr> {
+        match set_expr {
+            sqlparser::ast::SetExpr::Select(select) => {
+                self.add_conditions_to_select(select, context)
+            }
+            // UNION / INTERSECT / EXCEPT 的每个分支各自添加条件
+            sqlparser::ast::SetExpr::SetOperation { left, right, .. } => {
+                self.add_conditions_to_set_expr(left, context)?;
+                self.add_conditions_to_set_expr(right, context)
+            }
+            // 嵌套查询由 QueryVisitor 单独回调，这里不重复处理
+            _ => Ok(()),
+        }
+    }
+
+    /// 从表名中提取用于匹配跳过列表的表名（去除 schema 与引号）
+    fn table_name_of(&self, name: &sqlparser::ast::ObjectName) -> Option<String> {
+        // 使用标识符的原始值，去除 "、` 等方言相关的引号
+        name.0
+            .last()
+            .and_then(|part| part.as_ident())
+            .map(|ident| ident.value.to_lowercase())
+            .filter(|table_name| !table_name.is_empty())
+    }
+
+    /// 创建带表限定符的字段表达式
+    fn create_field_expr(&self, field_name: &str, qualifier: Option<&[sqlparser::ast::Ident]>) -> sqlparser::ast::Expr {
+        match qualifier {
+            Some(qualifier) => {
+                // 使用 表别名.字段名 或 表名.字段名 格式
+                let mut idents = qualifier.to_vec();
+                idents.push(sqlparser::ast::Ident::new(field_name));
+                sqlparser::ast::Expr::CompoundIdentifier(idents)
+            },
+            None => {
+                // 直接使用字段名
+                sqlparser::ast::Expr::Identifier(sqlparser::ast::Ident::new(field_name))
+            }
+        }
+    }
+
+    /// 为单张表生成默认过滤条件，字段名与取值按表的过滤字段配置生成，
+    /// 表结构注册表中登记了但缺少对应字段的表不添加该条件
+    fn build_table_conditions(
+        &self,
+        table_name: &str,
+        qualifier: Option<&[sqlparser::ast::Ident]>,
+        context: &RewriteContext,
+    ) -> Result<Vec<sqlparser::ast::Expr>, DbErr> {
+        let mut conditions = Vec::new();
+        let filter_columns = self.filter_columns_of(table_name);
+
+        // 添加软删除过滤条件
+        if self.enable_soft_delete
+            && !is_filter_disabled(FilterKind::SoftDelete)
+            && self.schema_registry.has_column(table_name, &filter_columns.soft_delete_column)
+        {
+            let field = self.create_field_expr(&filter_columns.soft_delete_column, qualifier);
+            conditions.push(match filter_columns.soft_delete_strategy {
+                SoftDeleteStrategy::Timestamp => sqlparser::ast::Expr::IsNull(Box::new(field)),
+                _ => sqlparser::ast::Expr::BinaryOp {
+                    left: Box::new(field),
+                    op: sqlparser::ast::BinaryOperator::Eq,
+                    right: Box::new(filter_value_expr(&filter_columns.not_deleted_value)),
+                },
+            });
+        }
+
+        // 添加租户过滤条件，严格模式下缺少租户时拒绝执行（系统身份除外）
+        if self.enable_tenant_filter
+            && !is_filter_disabled(FilterKind::Tenant)
+            && self.schema_registry.has_column(table_name, &filter_columns.tenant_column)
+        {
+            let tenant_values = filter_columns.visible_tenant_values(&context.auto_context);
+            if tenant_values.is_empty() {
+                if self.enable_strict_tenant && !is_system_scope() {
+                    return Err(HookError::MissingTenant {
+                        table: table_name.to_string(),
+                    }
+                    .into());
+                }
+            } else {
+                let subqueries = match filter_columns.tenant_value_source {
+                    TenantValueSource::TenantId => self.descendant_subqueries(&tenant_values, context),
+                    TenantValueSource::TenantName => Vec::new(),
+                };
+                let tenant_values = tenant_values
+                    .into_iter()
+                    .map(|(source, value)| context.binder.context_expr(source, value))
+                    .collect();
+                let mut condition = self.eq_or_in_condition(&filter_columns.tenant_column, qualifier, tenant_values);
+                if !subqueries.is_empty() {
+                    // 以子查询注入的租户层级：(column IN (...) OR column IN (下级租户子查询) ...)
+                    let combined = subqueries.into_iter().fold(condition, |left, subquery| sqlparser::ast::Expr::BinaryOp {
+                        left: Box::new(left),
+                        op: sqlparser::ast::BinaryOperator::Or,
+                        right: Box::new(sqlparser::ast::Expr::InSubquery {
+                            expr: Box::new(self.create_field_expr(&filter_columns.tenant_column, qualifier)),
+                            subquery: Box::new(subquery),
+                            negated: false,
+                        }),
+                    });
+                    condition = sqlparser::ast::Expr::Nested(Box::new(combined));
+                }
+                // 共享行只在查询时可见，UPDATE/DELETE 的目标表仍只能修改本租户的行
+                conditions.push(if filter_columns.has_shared_tenant() && !context.guarding_write.get() {
+                    self.with_shared_tenant_rows(condition, &filter_columns, qualifier)
+                } else {
+                    condition
+                });
+            }
+        }
+
+        // 添加数据权限条件，关闭数据权限过滤（包括关闭全部过滤）时不添加
+        if self.enable_data_scope
+            && !is_filter_disabled(FilterKind::DataScope)
+            && let Some(condition) = self.data_scope_condition(table_name, qualifier, &filter_columns, context)
+        {
+            conditions.push(condition);
+        }
+
+        Ok(conditions)
+    }
+
+    /// 按数据权限范围生成部门或创建人条件
+    ///
+    /// 表未配置（或缺少）对应字段时不添加；范围内没有可见的部门或用户时生成 `FALSE`，不可见任何行
+    fn data_scope_condition(
+        &self,
+        table_name: &str,
+        qualifier: Option<&[sqlparser::ast::Ident]>,
+        filter_columns: &FilterColumns,
+        context: &RewriteContext,
+    ) -> Option<sqlparser::ast::Expr> {
+        let auto_context = &context.auto_context;
+        let column = match auto_context.data_scope {
+            DataScope::All => return None,
+            DataScope::Own => filter_columns.owner_column.as_deref()?,
+            _ => filter_columns.dept_column.as_deref()?,
+        };
+        // 先检查字段再绑定参数，避免未使用的参数残留在参数列表中
+        if !self.schema_registry.has_column(table_name, column) {
+            return None;
+        }
+
+        let values: Vec<sqlparser::ast::Expr> = match auto_context.data_scope {
+            DataScope::Own => auto_context
+                .user_id
+                .clone()
+                .filter(|user_id| !user_id.is_empty())
+                .map(|user_id| context.binder.context_expr(ContextValue::UserId, user_id))
+                .into_iter()
+                .collect(),
+            _ => auto_context
+                .data_scope_dept_ids()
+                .into_iter()
+                .enumerate()
+                .map(|(index, dept_id)| context.binder.context_expr(ContextValue::ScopeDept(index), dept_id))
+                .collect(),
+        };
+        if values.is_empty() {
+            return Some(sqlparser::ast::Expr::Value(sqlparser::ast::Value::Boolean(false).with_empty_span()));
+        }
+        Some(self.eq_or_in_condition(column, qualifier, values))
+    }
+
+    /// 为每个可见租户生成查询下级租户的子查询，子查询中的占位符绑定对应的租户ID
+    ///
+    /// 子查询无法解析时记录错误并只保留 IN 列表，即不展开下级租户
+    fn descendant_subqueries(
+        &self,
+        tenant_values: &[(ContextValue, String)],
+        context: &RewriteContext,
+    ) -> Vec<sqlparser::ast::Query> {
+        let Some(subquery_sql) = self.tenant_hierarchy.as_ref().and_then(|hierarchy| hierarchy.descendants_subquery()) else {
+            return Vec::new();
+        };
+
+        let dialect = dialect_for_backend(context.backend);
+        // PostgreSQL 方言将 `?` 识别为运算符，统一按通用方言识别子查询中的占位符
+        let parse = |replace: &mut dyn FnMut() -> String| {
+            replace_placeholders(&subquery_sql, &GenericDialect {}, |_| Ok(replace())).and_then(|sql| {
+                Parser::new(dialect.as_ref())
+                    .try_with_sql(&sql)
+                    .and_then(|mut parser| parser.parse_query())
+                    .map(|query| *query)
+                    .map_err(|e| DbErr::Custom(e.to_string()))
+            })
+        };
+        // 先以 NULL 代替占位符校验子查询，避免无法解析时已绑定的参数残留在参数列表中
+        if let Err(e) = parse(&mut || "NULL".to_string()) {
+            log::error!("Failed to parse tenant hierarchy subquery: {}, error: {}", subquery_sql, e);
+            return Vec::new();
+        }
+
+        tenant_values
+            .iter()
+            .filter_map(|(source, value)| {
+                parse(&mut || context.binder.context_expr(*source, value.clone()).to_string()).ok()
+            })
+            .collect()
+    }
+
+    /// 生成取值条件：单个取值为 `column = value`，多个取值为 `column IN (...)`
+    fn eq_or_in_condition(
+        &self,
+        column: &str,
+        qualifier: Option<&[sqlparser::ast::Ident]>,
+        mut values: Vec<sqlparser::ast::Expr>,
+    ) -> sqlparser::ast::Expr {
+        let field = self.create_field_expr(column, qualifier);
+        if values.len() == 1 {
+            sqlparser::ast::Expr::BinaryOp {
+                left: Box::new(field),
+                op: sqlparser::ast::BinaryOperator::Eq,
+                right: Box::new(values.remove(0)),
+            }
+        } else {
+            sqlparser::ast::Expr::InList {
+                expr: Box::new(field),
+                list: values,
+                negated: false,
+            }
+        }
+    }
+
+    /// 以 OR 分支包含共享行：`(租户条件 OR column IS NULL OR column IN (shared...))`
+    fn with_shared_tenant_rows(
+        &self,
+        condition: sqlparser::ast::Expr,
+        filter_columns: &FilterColumns,
+        qualifier: Option<&[sqlparser::ast::Ident]>,
+    ) -> sqlparser::ast::Expr {
+        let mut branches = Vec::new();
+        if filter_columns.shared_tenant_null {
+            branches.push(sqlparser::ast::Expr::IsNull(Box::new(
+                self.create_field_expr(&filter_columns.tenant_column, qualifier),
+            )));
+        }
+        if !filter_columns.shared_tenant_values.is_empty() {
+            branches.push(self.eq_or_in_condition(
+                &filter_columns.tenant_column,
+                qualifier,
+                filter_columns.shared_tenant_values.iter().map(filter_value_expr).collect(),
+            ));
+        }
+
+        let combined = branches.into_iter().fold(condition, |left, right| sqlparser::ast::Expr::BinaryOp {
+            left: Box::new(left),
+            op: sqlparser::ast::BinaryOperator::Or,
+            right: Box::new(right),
+        });
+        sqlparser::ast::Expr::Nested(Box::new(combined))
+    }
+
+    /// 为 FROM/JOIN 中的单个表因子生成过滤条件
+    ///
+    /// 普通表按跳过列表决定是否过滤；派生表（子查询）由 QueryVisitor 在其内部作用域中
+    /// 添加条件，不在外层生成条件；括号包裹的连接递归处理
+    fn conditions_for_table_factor(
+        &self,
+        relation: &mut sqlparser::ast::TableFactor,
+        qualify: bool,
+        context: &RewriteContext,
+    ) -> Result<Vec<sqlparser::ast::Expr>, DbErr> {
+        match relation {
+            // 带参数的是表函数（如 `generate_series(1, 10)`），不是普通表
+            sqlparser::ast::TableFactor::Table { args: Some(_), .. } => Ok(Vec::new()),
+            sqlparser::ast::TableFactor::Table { name, alias, .. } => {
+                let Some(table_name) = self.table_name_of(name) else {
+                    return Ok(Vec::new());
+                };
+                // 检查是否需要跳过该表的默认过滤
+                if self.should_skip_table(&table_name) {
+                    return Ok(Vec::new());
+                }
+                // 引用 CTE 的表由 CTE 定义内部负责过滤
+                if context.is_cte(name) {
+                    return Ok(Vec::new());
+                }
+
+                // 有别名时使用别名；多表查询中没有别名时使用表名限定字段，避免歧义
+                let qualifier = match alias {
+                    Some(alias) => Some(vec![alias.name.clone()]),
+                    None if qualify => Some(name.0.iter().filter_map(|part| part.as_ident().cloned()).collect()),
+                    None => None,
+                };
+                self.build_table_conditions(&table_name, qualifier.as_deref(), context)
+            }
+            sqlparser::ast::TableFactor::NestedJoin { table_with_joins, .. } => {
+                let mut conditions = Vec::new();
+                self.add_conditions_to_table_with_joins(table_with_joins, true, &mut conditions, context)?;
+                Ok(conditions)
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// 为 FROM 中的一项（主表及其 JOIN）添加过滤条件
+    ///
+    /// 外连接中可为空一侧的表，条件放入该 JOIN 的 ON 子句，避免把外连接变成内连接；USING、NATURAL 连接
+    /// 没有 ON 子句，可为空一侧的表改为带条件的派生表。其余表的条件收集到 `where_conditions` 中，由调用方放入 WHERE 子句
+    fn add_conditions_to_table_with_joins(
+        &self,
+        table_with_joins: &mut sqlparser::ast::TableWithJoins,
+        qualify: bool,
+        where_conditions: &mut Vec<sqlparser::ast::Expr>,
+        context: &RewriteContext,
+    ) -> Result<(), DbErr> {
+        use sqlparser::ast::JoinOperator;
+
+        // 当前 JOIN 左侧所有表的条件
+        let mut left_conditions = self.conditions_for_table_factor(&mut table_with_joins.relation, qualify, context)?;
+
+        for (index, join) in table_with_joins.joins.iter_mut().enumerate() {
+            let join_conditions = self.conditions_for_table_factor(&mut join.relation, qualify, context)?;
+            match &mut join.join_operator {
+                // 左外连接：右侧表可为空，条件放入 ON
+                JoinOperator::Left(constraint) | JoinOperator::LeftOuter(constraint) => {
+                    if Self::accepts_on_conditions(constraint) {
+                        Self::add_conditions_to_join_constraint(constraint, join_conditions);
+                    } else {
+                        Self::wrap_in_filtered_table(&mut join.relation, join_conditions)?;
+                    }
+                }
+                // 右外连接：左侧已有的表可为空，其条件放入 ON，右侧表条件放入 WHERE
+                JoinOperator::Right(constraint) | JoinOperator::RightOuter(constraint) => {
+                    let nullable = std::mem::take(&mut left_conditions);
+                    if Self::accepts_on_conditions(constraint) {
+                        Self::add_conditions_to_join_constraint(constraint, nullable);
+                    } else if index == 0 {
+                        Self::wrap_in_filtered_table(&mut table_with_joins.relation, nullable)?;
+                    } else if !nullable.is_empty() {
+                        // 左侧由多张表连接而成，无法改为单个派生表
+                        return Err(HookError::UnfilterableJoin {
+                            relation: join.to_string(),
+                        }
+                        .into());
+                    }
+                    left_conditions = join_conditions;
+                }
+                // 内连接等其余连接方式放入 WHERE；全外连接两侧都保留，
+                // 同样放入 WHERE，宁可少返回数据也不泄露其他租户或已删除的数据
+                _ => left_conditions.extend(join_conditions),
+            }
+        }
+
+        where_conditions.extend(left_conditions);
+        Ok(())
+    }
+
+    /// JOIN 是否可以通过 ON 子句追加条件（USING、NATURAL 连接不可以）
+    fn accepts_on_conditions(constraint: &sqlparser::ast::JoinConstraint) -> bool {
+        matches!(
+            constraint,
+            sqlparser::ast::JoinConstraint::On(_) | sqlparser::ast::JoinConstraint::None
+        )
+    }
+
+    /// 将条件追加到 JOIN 的 ON 子句中
+    fn add_conditions_to_join_constraint(
+        constraint: &mut sqlparser::ast::JoinConstraint,
+        conditions: Vec<sqlparser::ast::Expr>,
+    ) {
+        let Some(combined_condition) = Self::combine_conditions(conditions) else {
+            return;
+        };
+        match constraint {
+            sqlparser::ast::JoinConstraint::On(on) => Self::and_condition(on, combined_condition),
+            _ => *constraint = sqlparser::ast::JoinConstraint::On(combined_condition),
+        }
+    }
+
+    /// 将外连接可为空一侧的表改为带过滤条件的派生表：`(SELECT * FROM users u WHERE ...) AS u`
+    ///
+    /// 派生表内保留原表及别名，条件中的限定名不变；外层使用原别名（没有别名时为表名），连接方式保持不变。
+    /// 可为空一侧不是单张表时无法改写，拒绝执行
+    fn wrap_in_filtered_table(
+        relation: &mut sqlparser::ast::TableFactor,
+        conditions: Vec<sqlparser::ast::Expr>,
+    ) -> Result<(), DbErr> {
+        let Some(condition) = Self::combine_conditions(conditions) else {
+            return Ok(());
+        };
+        let unfilterable = |relation: &sqlparser::ast::TableFactor| -> DbErr {
+            HookError::UnfilterableJoin {
+                relation: relation.to_string(),
+            }
+            .into()
+        };
+        let outer_alias = match relation {
+            sqlparser::ast::TableFactor::Table { alias: Some(alias), .. } => sqlparser::ast::TableAlias {
+                explicit: alias.explicit,
+                name: alias.name.clone(),
+                columns: Vec::new(),
+            },
+            sqlparser::ast::TableFactor::Table { name, alias: None, .. } => {
+                let Some(table) = name.0.last().and_then(|part| part.as_ident()) else {
+                    return Err(unfilterable(relation));
+                };
+                sqlparser::ast::TableAlias {
+                    explicit: true,
+                    name: table.clone(),
+                    columns: Vec::new(),
+                }
+            }
+            _ => return Err(unfilterable(relation)),
+        };
+
+        let mut subquery = Parser::new(&GenericDialect {})
+            .try_with_sql("SELECT * FROM t")
+            .and_then(|mut parser| parser.parse_query())
+            .map_err(|e| DbErr::Custom(e.to_string()))?;
+        // 派生表的 FROM 中换入原表，换出的模板表随即被派生表替换
+        if let sqlparser::ast::SetExpr::Select(select) = subquery.body.as_mut() {
+            std::mem::swap(&mut select.from[0].relation, relation);
+            select.selection = Some(condition);
+        }
+        *relation = sqlparser::ast::TableFactor::Derived {
+            lateral: false,
+            subquery,
+            alias: Some(outer_alias),
+        };
+        Ok(())
+    }
+
+    /// 将多个条件以 AND 合并，多个条件时使用括号包裹
+    fn combine_conditions(conditions: Vec<sqlparser::ast::Expr>) -> Option<sqlparser::ast::Expr> {
+        let count = conditions.len();
+        let combined = conditions.into_iter().reduce(|left, right| {
+            sqlparser::ast::Expr::BinaryOp {
+                left: Box::new(left),
+                op: sqlparser::ast::BinaryOperator::And,
+                right: Box::new(right),
+            }
+        })?;
+        if count > 1 {
+            Some(sqlparser::ast::Expr::Nested(Box::new(combined)))
+        } else {
+            Some(combined)
+        }
+    }
+
+    /// 将条件以 AND 追加到已有表达式之后
+    fn and_condition(existing: &mut sqlparser::ast::Expr, condition: sqlparser::ast::Expr) {
+        let placeholder = sqlparser::ast::Expr::Value(sqlparser::ast::Value::Null.with_empty_span());
+        let mut left = std::mem::replace(existing, placeholder);
+        // 原条件中顶层的 OR/XOR 优先级低于 AND，需要加括号
+        if let sqlparser::ast::Expr::BinaryOp {
+            op: sqlparser::ast::BinaryOperator::Or | sqlparser::ast::BinaryOperator::Xor,
+            ..
+        } = left
+        {
+            left = sqlparser::ast::Expr::Nested(Box::new(left));
+        }
+        *existing = sqlparser::ast::Expr::BinaryOp {
+            left: Box::new(left),
+            op: sqlparser::ast::BinaryOperator::And,
+            right: Box::new(condition),
+        };
+    }
+
+    /// 向Select语句中添加默认条件
+    fn add_conditions_to_select(
+        &self,
+        select: &mut sqlparser::ast::Select,
+        context: &RewriteContext,
+    ) -> Result<(), DbErr> {
+        // 多表查询时，没有别名的表也需要使用表名限定字段
+        let qualify = select.from.len() > 1 || select.from.iter().any(|table| !table.joins.is_empty());
+
+        let mut conditions = Vec::new();
+        for table_with_joins in &mut select.from {
+            self.add_conditions_to_table_with_joins(table_with_joins, qualify, &mut conditions, context)?;
+        }
+
+        Self::add_conditions_to_selection(&mut select.selection, conditions);
+        Ok(())
+    }
+
+    /// 将条件合并并添加到 WHERE 子句
+    fn add_conditions_to_selection(selection: &mut Option<sqlparser::ast::Expr>, conditions: Vec<sqlparser::ast::Expr>) {
+        if let Some(combined_condition) = Self::combine_conditions(conditions) {
+            match selection {
+                Some(selection) => Self::and_condition(selection, combined_condition),
+                None => *selection = Some(combined_condition),
+            }
+        }
+    }
+}
+
+/// 绑定参数收集器
+///
+/// 绑定模式下保留原语句的参数，改写时新增的值（租户ID、审计字段等）以占位符形式追加在其后；
+/// 内联模式下直接生成字面量
+struct ParamBinder {
+    /// 占位符前缀，内联模式为 `None`
+    prefix: Option<char>,
+
+    /// 原语句参数及新增参数的来源，新增参数在生成语句时才从上下文取值
+    values: RefCell<Vec<TemplateValue>>,
+
+    /// 改写过程中是否发生解析失败
+    parse_failed: Cell<bool>,
+
+    /// 内联模式下首次读取的当前时间，同一语句中的时间字段取值相同
+    now: Cell<Option<DateTime>>,
+}
+
+impl ParamBinder {
+    /// 创建内联模式的收集器
+    fn inline() -> Self {
+        Self {
+            prefix: None,
+            values: RefCell::new(Vec::new()),
+            parse_failed: Cell::new(false),
+            now: Cell::new(None),
+        }
+    }
+
+    /// 创建绑定模式的收集器，PostgreSQL 使用 `$n` 占位符，其余数据库使用编号的 `?n` 占位符
+    fn bound(backend: DatabaseBackend, original_count: usize) -> Self {
+        let prefix = match backend {
+            DatabaseBackend::Postgres => '$',
+            _ => '?',
+        };
+        Self {
+            prefix: Some(prefix),
+            values: RefCell::new((0..original_count).map(TemplateValue::Original).collect()),
+            parse_failed: Cell::new(false),
+            now: Cell::new(None),
+        }
+    }
+
+    /// 创建取自上下文的字符串值表达式，内联模式使用 `value` 生成字面量
+    fn context_expr(&self, source: ContextValue, value: String) -> sqlparser::ast::Expr {
+        match self.prefix {
+            Some(prefix) => self.bind(prefix, TemplateValue::Context(source)),
+            None => sqlparser::ast::Expr::Value(sqlparser::ast::Value::SingleQuotedString(value).with_empty_span()),
+        }
+    }
+
+    /// 使用全局时钟创建当前时间的值表达式
+    fn current_time_expr(&self) -> sqlparser::ast::Expr {
+        match self.prefix {
+            Some(prefix) => self.bind(prefix, TemplateValue::Context(ContextValue::CurrentTime)),
+            None => sqlparser::ast::Expr::Value(
+                sqlparser::ast::Value::SingleQuotedString(self.now().format("%Y-%m-%d %H:%M:%S%.6f").to_string())
+                    .with_empty_span(),
+            ),
+        }
+    }
+
+    /// 全局时钟的当前时间，同一收集器只读取一次
+    fn now(&self) -> DateTime {
+        let now = self.now.get().unwrap_or_else(crate::clock::current_time);
+        self.now.set(Some(now));
+        now
+    }
+
+    /// 追加参数并返回对应的占位符
+    fn bind(&self, prefix: char, value: TemplateValue) -> sqlparser::ast::Expr {
+        let mut values = self.values.borrow_mut();
+        values.push(value);
+        sqlparser::ast::Expr::Value(
+            sqlparser::ast::Value::Placeholder(format!("{}{}", prefix, values.len())).with_empty_span(),
+        )
+    }
+
+    /// 取出全部参数的来源
+    fn take_values(&self) -> Vec<TemplateValue> {
+        self.values.take()
+    }
+}
+
+/// 按出现顺序替换 SQL 中的占位符，字符串与注释中的内容不受影响
+fn replace_placeholders<F>(sql: &str, dialect: &dyn Dialect, mut replace: F) -> Result<String, DbErr>
+where
+    F: FnMut(&str) -> Result<String, DbErr>,
+{
+    let tokens = Tokenizer::new(dialect, sql)
+        .tokenize_with_location()
+        .map_err(|e| DbErr::Custom(format!("Failed to tokenize SQL: {}", e)))?;
+
+    // 将词法位置（行、列均从 1 开始，列按字符计数）换算为字节偏移
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(sql.match_indices('\n').map(|(index, _)| index + 1))
+        .collect();
+    let offset = |location: Location| {
+        let line_start = line_starts[(location.line as usize).saturating_sub(1)];
+        sql[line_start..]
+            .char_indices()
+            .nth((location.column as usize).saturating_sub(1))
+            .map_or(sql.len(), |(index, _)| line_start + index)
+    };
+
+    let mut result = String::with_capacity(sql.len());
+    let mut last = 0;
+    for token in tokens {
+        if let Token::Placeholder(placeholder) = &token.token {
+            let start = offset(token.span.start);
+            result.push_str(&sql[last..start]);
+            result.push_str(&replace(placeholder)?);
+            last = offset(token.span.end);
+        }
+    }
+    result.push_str(&sql[last..]);
+    Ok(result)
+}
+
+/// 单条语句改写过程中共享的上下文
+struct RewriteContext<'a> {
+    /// 当前请求的自动字段上下文
+    auto_context: AutoFieldContext,
+
+    /// 当前处理的查询作用域中可见的 CTE，引用 CTE 的表不再添加过滤条件
+    cte_scope: RefCell<CteScope>,
+
+    /// 数据库类型
+    backend: DatabaseBackend,
+
+    /// 新增值的字面量或占位符生成器
+    binder: &'a ParamBinder,
+
+    /// 是否正在为 UPDATE/DELETE 的目标表添加条件，此时不包含共享行
+    guarding_write: Cell<bool>,
+
+    /// DELETE 改写为软删除 UPDATE 时保留的 ORDER BY，生成 SQL 时拼接
+    update_order_by: RefCell<Vec<sqlparser::ast::OrderByExpr>>,
+}
+
+impl<'a> RewriteContext<'a> {
+    /// 为待改写的语句创建上下文
+    fn new(backend: DatabaseBackend, binder: &'a ParamBinder, auto_context: AutoFieldContext) -> Self {
+        Self {
+            auto_context,
+            cte_scope: RefCell::new(CteScope::default()),
+            backend,
+            binder,
+            guarding_write: Cell::new(false),
+            update_order_by: RefCell::new(Vec::new()),
+        }
     }
 
-    /// 检查表是否需要跳过默认过滤
-    fn should_skip_table(&self, table_name: &str) -> bool {
-        let skip_tables = self.skip_tables.read();
-        skip_tables.contains(&table_name.to_lowercase())
+    /// 检查表名是否引用了当前作用域中可见的 CTE
+    fn is_cte(&self, name: &sqlparser::ast::ObjectName) -> bool {
+        match name.0.as_slice() {
+            [part] => part.as_ident().is_some_and(|ident| self.cte_scope.borrow().contains(&ident.value)),
+            _ => false,
+        }
     }
+}
 
-    /// 解析 SQL 并添加默认查询条件
-    fn add_default_conditions(&self, sql: &str) -> Result<String, DbErr> {
-        let dialect = GenericDialect {};
+/// 查询作用域中可见的 CTE 名称（小写），内层查询可见外层查询定义的 CTE
+#[derive(Debug, Clone, Default)]
+pub struct CteScope {
+    names: Vec<String>,
+}
 
-        match Parser::parse_sql(&dialect, sql) {
-            Ok(mut statements) => {
-                if statements.is_empty() {
-                    return Ok(sql.to_string());
-                }
+impl CteScope {
+    /// 根据 WITH 子句创建作用域，用于 WITH 开头的 INSERT/UPDATE/DELETE
+    fn of(with: Option<&sqlparser::ast::With>) -> Self {
+        Self {
+            names: with
+                .map(|with| with.cte_tables.iter().map(|cte| cte.alias.name.value.to_lowercase()).collect())
+                .unwrap_or_default(),
+        }
+    }
 
-                let statement = &mut statements[0];
+    /// 检查名称是否为可见的 CTE
+    pub fn contains(&self, name: &str) -> bool {
+        self.names.iter().any(|cte_name| cte_name.eq_ignore_ascii_case(name))
+    }
+}
 
-                // 只处理 SELECT 语句
-                if let sqlparser::ast::Statement::Query(query) = statement {
-                    if let Some(table_name) = self.extract_table_name(query) {
-                        // 检查是否需要跳过该表的默认过滤
-                        if self.should_skip_table(&table_name) {
-                            return Ok(sql.to_string());
-                        }
+/// SQL 语法树遍历器
+///
+/// 深度优先查找查询中所有嵌套的 Query（FROM 子查询、表函数参数、CTE、WHERE/HAVING/SELECT 列表、
+/// ORDER BY 及 LIMIT 中的 `EXISTS (SELECT ...)`、`IN (SELECT ...)`、标量子查询等），按子查询先于外层查询的顺序回调，
+/// 使每个查询都在其自身作用域中处理；回调时同时给出该查询中可见的 CTE
+pub struct QueryVisitor<F>
+where
+    F: FnMut(&mut sqlparser::ast::Query, &CteScope) -> Result<(), DbErr>,
+{
+    on_query: F,
 
-                        // 添加默认查询条件
-                        self.add_conditions_to_query(query, &table_name)?;
-                    }
-                }
+    /// 当前遍历位置可见的 CTE
+    cte_scope: CteScope,
+}
 
-                Ok(statements[0].to_string())
-            }
-            Err(e) => {
-                log::warn!("Failed to parse SQL: {}, error: {}", sql, e);
-                // 解析失败时返回原始 SQL
-                Ok(sql.to_string())
-            }
+impl<F> QueryVisitor<F>
+where
+    F: FnMut(&mut sqlparser::ast::Query, &CteScope) -> Result<(), DbErr>,
+{
+    /// 创建遍历器，`on_query` 会对每个查询调用一次
+    pub fn new(on_query: F) -> Self {
+        Self {
+            on_query,
+            cte_scope: CteScope::default(),
         }
     }
 
-    /// 从查询中提取表名，支持嵌套查询
-    fn extract_table_name(&self, query: &sqlparser::ast::Query) -> Option<String> {
-        self.extract_table_name_from_set_expr(&query.body)
-    }
-    
-    /// 从SetExpr中提取表名，支持递归处理嵌套查询
-    fn extract_table_name_from_set_expr(&self, set_expr: &sqlparser::ast::SetExpr) -> Option<String> {
-        match set_expr {
-            sqlparser::ast::SetExpr::Select(select) => {
-                // 检查是否有FROM子句
-                if !select.from.is_empty() {
-                    // 从第一个表中提取表名
-                    if let sqlparser::ast::TableFactor::Table { name, .. } = &select.from[0].relation {
-                        if let Some(last_ident) = name.0.last() {
-                            let mut table_name = last_ident.to_string().to_lowercase();
-                            // 移除可能存在的引号
-                            if table_name.starts_with('"') && table_name.ends_with('"') {
-                                table_name = table_name[1..table_name.len()-1].to_string();
-                            }
-                            if !table_name.is_empty() {
-                                return Some(table_name);
-                            }
+    /// 遍历语句中的全部查询，包括 INSERT 的数据来源以及 UPDATE、DELETE 中的子查询
+    pub fn visit_statement(&mut self, statement: &mut sqlparser::ast::Statement) -> Result<(), DbErr> {
+        match statement {
+            sqlparser::ast::Statement::Query(query) => self.visit_query(query),
+            sqlparser::ast::Statement::Insert(insert) => {
+                if let Some(source) = &mut insert.source {
+                    self.visit_query(source)?;
+                }
+                for assignment in &mut insert.assignments {
+                    self.visit_expr(&mut assignment.value)?;
+                }
+                // ON DUPLICATE KEY UPDATE、ON CONFLICT DO UPDATE 中的赋值及条件
+                match &mut insert.on {
+                    Some(sqlparser::ast::OnInsert::DuplicateKeyUpdate(assignments)) => {
+                        for assignment in assignments {
+                            self.visit_expr(&mut assignment.value)?;
+                        }
+                    }
+                    Some(sqlparser::ast::OnInsert::OnConflict(sqlparser::ast::OnConflict {
+                        action: sqlparser::ast::OnConflictAction::DoUpdate(do_update),
+                        ..
+                    })) => {
+                        for assignment in &mut do_update.assignments {
+                            self.visit_expr(&mut assignment.value)?;
+                        }
+                        if let Some(expr) = &mut do_update.selection {
+                            self.visit_expr(expr)?;
                         }
                     }
-                    // 检查是否是子查询
-                    else if let sqlparser::ast::TableFactor::Derived { subquery, .. } = &select.from[0].relation {
-                        // 递归处理子查询
-                        return self.extract_table_name(subquery);
+                    _ => {}
+                }
+                Ok(())
+            }
+            sqlparser::ast::Statement::Update(update) => {
+                self.visit_table_with_joins(&mut update.table)?;
+                for assignment in &mut update.assignments {
+                    self.visit_expr(&mut assignment.value)?;
+                }
+                if let Some(
+                    sqlparser::ast::UpdateTableFromKind::BeforeSet(from)
+                    | sqlparser::ast::UpdateTableFromKind::AfterSet(from),
+                ) = &mut update.from
+                {
+                    for table_with_joins in from {
+                        self.visit_table_with_joins(table_with_joins)?;
                     }
                 }
-                None
+                if let Some(expr) = &mut update.selection {
+                    self.visit_expr(expr)?;
+                }
+                if let Some(expr) = &mut update.limit {
+                    self.visit_expr(expr)?;
+                }
+                Ok(())
             }
-            // 处理其他类型的SetExpr，如子查询
-            sqlparser::ast::SetExpr::Query(query) => {
-                // 递归处理子查询
-                self.extract_table_name(query)
+            sqlparser::ast::Statement::Delete(delete) => {
+                let (sqlparser::ast::FromTable::WithFromKeyword(from)
+                | sqlparser::ast::FromTable::WithoutKeyword(from)) = &mut delete.from;
+                for table_with_joins in from.iter_mut().chain(delete.using.iter_mut().flatten()) {
+                    self.visit_table_with_joins(table_with_joins)?;
+                }
+                if let Some(expr) = &mut delete.selection {
+                    self.visit_expr(expr)?;
+                }
+                self.visit_order_by_exprs(&mut delete.order_by)?;
+                if let Some(expr) = &mut delete.limit {
+                    self.visit_expr(expr)?;
+                }
+                Ok(())
             }
-            _ => None
+            _ => Ok(()),
         }
     }
 
-    /// 向查询中添加默认条件
-    fn add_conditions_to_query(
-        &self,
-        query: &mut sqlparser::ast::Query,
-        _table_name: &str,
-    ) -> Result<(), DbErr> {
-        // 处理 COUNT 查询，将条件添加到内部子查询
-        if let sqlparser::ast::SetExpr::Select(select) = &mut *query.body {
-            // 检查是否是 COUNT 查询（SELECT COUNT(*) FROM ...）
-            if self.is_count_query(select) {
-                // 遍历 FROM 子句，查找子查询
-                for table in &mut select.from {
-                    if let sqlparser::ast::TableFactor::Derived { subquery, .. } = &mut table.relation {
-                        // 向内部子查询添加条件
-                        self.add_conditions_to_query(subquery, "")?;
-                    }
-                }
-                return Ok(());
+    /// 遍历查询及其全部嵌套查询
+    ///
+    /// WITH 中定义的 CTE 只在该查询内可见；非递归 CTE 的定义只能引用在其之前定义的 CTE，
+    /// 其中与自身同名的表仍指向真实的表
+    pub fn visit_query(&mut self, query: &mut sqlparser::ast::Query) -> Result<(), DbErr> {
+        let outer = self.cte_scope.names.len();
+        if let Some(with) = &mut query.with {
+            let names: Vec<String> = with.cte_tables.iter().map(|cte| cte.alias.name.value.to_lowercase()).collect();
+            let recursive = with.recursive;
+            for (index, cte) in with.cte_tables.iter_mut().enumerate() {
+                let visible = if recursive { names.len() } else { index };
+                self.cte_scope.names.extend_from_slice(&names[..visible]);
+                self.visit_query(&mut cte.query)?;
+                self.cte_scope.names.truncate(outer);
             }
+            self.cte_scope.names.extend(names);
         }
-        
-        // 非 COUNT 查询，直接向查询体添加条件
-        self.add_conditions_to_set_expr(&mut query.body)
-    }
-    
-    /// 检查是否是 COUNT 查询
-    fn is_count_query(&self, select: &sqlparser::ast::Select) -> bool {
-        // 检查 SELECT 列表是否只有 COUNT(*)
-        if select.projection.len() != 1 {
-            return false;
+        self.visit_set_expr(&mut query.body)?;
+        // ORDER BY、LIMIT/OFFSET、FETCH 中同样可能包含子查询
+        if let Some(sqlparser::ast::OrderBy {
+            kind: sqlparser::ast::OrderByKind::Expressions(order_by),
+            ..
+        }) = &mut query.order_by
+        {
+            self.visit_order_by_exprs(order_by)?;
         }
-        
-        match &select.projection[0] {
-            sqlparser::ast::SelectItem::ExprWithAlias { expr, .. } | sqlparser::ast::SelectItem::UnnamedExpr(expr) => {
-                if let sqlparser::ast::Expr::Function(func) = expr {
-                    // 检查函数名是否为 COUNT
-                    if func.name.0.last().map_or(false, |ident| ident.to_string().eq_ignore_ascii_case("COUNT")) {
-                        // 检查是否包含 COUNT(*) 或 COUNT(1)
-                        // 简化检查，不依赖 FunctionArguments 的内部结构
-                        let func_str = func.to_string();
-                        return func_str.eq_ignore_ascii_case("COUNT(*)") || func_str.eq_ignore_ascii_case("count(*)");
-                    }
+        match &mut query.limit_clause {
+            Some(sqlparser::ast::LimitClause::LimitOffset { limit, offset, limit_by }) => {
+                for expr in limit.iter_mut().chain(offset.as_mut().map(|offset| &mut offset.value)) {
+                    self.visit_expr(expr)?;
                 }
+                self.visit_exprs(limit_by)?;
             }
-            _ => {}
+            Some(sqlparser::ast::LimitClause::OffsetCommaLimit { offset, limit }) => {
+                self.visit_expr(offset)?;
+                self.visit_expr(limit)?;
+            }
+            None => {}
+        }
+        if let Some(expr) = query.fetch.as_mut().and_then(|fetch| fetch.quantity.as_mut()) {
+            self.visit_expr(expr)?;
         }
-        
-        false
+        let result = (self.on_query)(query, &self.cte_scope);
+        self.cte_scope.names.truncate(outer);
+        result
     }
-    
-    /// 向SetExpr中添加默认条件，支持递归处理嵌套查询
-    fn add_conditions_to_set_expr(
-        &self,
-        set_expr: &mut sqlparser::ast::SetExpr,
-    ) -> Result<(), DbErr> {
+
+    fn visit_set_expr(&mut self, set_expr: &mut sqlparser::ast::SetExpr) -> Result<(), DbErr> {
         match set_expr {
-            sqlparser::ast::SetExpr::Select(select) => {
-                self.add_conditions_to_select(select)
+            sqlparser::ast::SetExpr::Select(select) => self.visit_select(select),
+            sqlparser::ast::SetExpr::Query(query) => self.visit_query(query),
+            sqlparser::ast::SetExpr::SetOperation { left, right, .. } => {
+                self.visit_set_expr(left)?;
+                self.visit_set_expr(right)
             }
-            sqlparser::ast::SetExpr::Query(query) => {
-                // 递归处理嵌套查询
-                self.add_conditions_to_query(query, "")
+            sqlparser::ast::SetExpr::Values(values) => {
+                for row in &mut values.rows {
+                    self.visit_exprs(row)?;
+                }
+                Ok(())
             }
+            sqlparser::ast::SetExpr::Insert(statement)
+            | sqlparser::ast::SetExpr::Update(statement)
+            | sqlparser::ast::SetExpr::Delete(statement) => self.visit_statement(statement),
             _ => Ok(()),
         }
     }
-    
-    /// 从Select语句中提取表别名（仅提取别名，不提取表名）
-    fn extract_table_alias_or_name(&self, select: &sqlparser::ast::Select) -> Option<String> {
-        if select.from.is_empty() {
-            return None;
+
+    fn visit_select(&mut self, select: &mut sqlparser::ast::Select) -> Result<(), DbErr> {
+        for item in &mut select.projection {
+            match item {
+                sqlparser::ast::SelectItem::UnnamedExpr(expr)
+                | sqlparser::ast::SelectItem::ExprWithAlias { expr, .. } => self.visit_expr(expr)?,
+                _ => {}
+            }
         }
-        
-        let table = &select.from[0];
-        match &table.relation {
-            sqlparser::ast::TableFactor::Table { alias, .. } => {
-                // 仅提取表别名，没有别名时返回None
-                if let Some(alias) = alias {
-                    return Some(alias.name.to_string());
-                }
-                None
-            },
-            _ => None,
+        for table_with_joins in &mut select.from {
+            self.visit_table_with_joins(table_with_joins)?;
+        }
+        if let Some(expr) = &mut select.prewhere {
+            self.visit_expr(expr)?;
+        }
+        if let Some(expr) = &mut select.selection {
+            self.visit_expr(expr)?;
+        }
+        if let sqlparser::ast::GroupByExpr::Expressions(exprs, _) = &mut select.group_by {
+            self.visit_exprs(exprs)?;
+        }
+        if let Some(expr) = &mut select.having {
+            self.visit_expr(expr)?;
         }
+        if let Some(expr) = &mut select.qualify {
+            self.visit_expr(expr)?;
+        }
+        Ok(())
     }
-    
-    /// 创建带表别名的字段表达式
-    fn create_field_expr(&self, field_name: &str, table_alias: Option<&str>) -> sqlparser::ast::Expr {
-        match table_alias {
-            Some(alias) => {
-                // 使用表别名.字段名格式
-                sqlparser::ast::Expr::CompoundIdentifier(vec![
-                    sqlparser::ast::Ident::new(alias),
-                    sqlparser::ast::Ident::new(field_name)
-                ])
-            },
-            None => {
-                // 直接使用字段名
-                sqlparser::ast::Expr::Identifier(sqlparser::ast::Ident::new(field_name))
+
+    fn visit_table_with_joins(&mut self, table_with_joins: &mut sqlparser::ast::TableWithJoins) -> Result<(), DbErr> {
+        self.visit_table_factor(&mut table_with_joins.relation)?;
+        for join in &mut table_with_joins.joins {
+            self.visit_table_factor(&mut join.relation)?;
+            let constraint = match &mut join.join_operator {
+                sqlparser::ast::JoinOperator::Join(constraint)
+                | sqlparser::ast::JoinOperator::Inner(constraint)
+                | sqlparser::ast::JoinOperator::Left(constraint)
+                | sqlparser::ast::JoinOperator::LeftOuter(constraint)
+                | sqlparser::ast::JoinOperator::Right(constraint)
+                | sqlparser::ast::JoinOperator::RightOuter(constraint)
+                | sqlparser::ast::JoinOperator::FullOuter(constraint)
+                | sqlparser::ast::JoinOperator::CrossJoin(constraint)
+                | sqlparser::ast::JoinOperator::Semi(constraint)
+                | sqlparser::ast::JoinOperator::LeftSemi(constraint)
+                | sqlparser::ast::JoinOperator::RightSemi(constraint)
+                | sqlparser::ast::JoinOperator::Anti(constraint)
+                | sqlparser::ast::JoinOperator::LeftAnti(constraint)
+                | sqlparser::ast::JoinOperator::RightAnti(constraint)
+                | sqlparser::ast::JoinOperator::StraightJoin(constraint)
+                | sqlparser::ast::JoinOperator::AsOf { constraint, .. } => Some(constraint),
+                _ => None,
+            };
+            if let Some(sqlparser::ast::JoinConstraint::On(expr)) = constraint {
+                self.visit_expr(expr)?;
             }
         }
+        Ok(())
     }
-    
-    /// 向Select语句中添加默认条件
-    fn add_conditions_to_select(
-        &self,
-        select: &mut sqlparser::ast::Select,
-    ) -> Result<(), DbErr> {
-        let mut conditions = Vec::new();
-        
-        // 提取表别名或表名
-        let table_alias = self.extract_table_alias_or_name(select);
-        let table_alias_ref = table_alias.as_deref();
 
-        // 添加软删除过滤条件
-        if self.enable_soft_delete {
-            conditions.push(sqlparser::ast::Expr::BinaryOp {
-                left: Box::new(self.create_field_expr("delete_flag", table_alias_ref)),
-                op: sqlparser::ast::BinaryOperator::Eq,
-                right: Box::new(sqlparser::ast::Expr::Value(sqlparser::ast::Value::Number("0".to_string(), false).with_empty_span())),
-            });
-        }
+    fn visit_table_factor(&mut self, relation: &mut sqlparser::ast::TableFactor) -> Result<(), DbErr> {
+        use sqlparser::ast::TableFactor;
 
-        // 添加租户过滤条件
-        if self.enable_tenant_filter {
-            let context = crate::auto_field_trait::AutoFieldContext::current_safe();
-            if let Some(tenant_id) = context.tenant_id {
-                if !tenant_id.is_empty() {
-                    conditions.push(sqlparser::ast::Expr::BinaryOp {
-                        left: Box::new(self.create_field_expr("tenant_id", table_alias_ref)),
-                        op: sqlparser::ast::BinaryOperator::Eq,
-                        right: Box::new(sqlparser::ast::Expr::Value(sqlparser::ast::Value::SingleQuotedString(tenant_id).with_empty_span())),
-                    });
+        match relation {
+            TableFactor::Table { args: Some(args), .. } => self.visit_function_args(&mut args.args),
+            TableFactor::Derived { subquery, .. } => self.visit_query(subquery),
+            TableFactor::NestedJoin { table_with_joins, .. } => self.visit_table_with_joins(table_with_joins),
+            // 表函数的参数中可能包含子查询
+            TableFactor::TableFunction { expr, .. } => self.visit_expr(expr),
+            TableFactor::Function { args, .. } => self.visit_function_args(args),
+            TableFactor::UNNEST { array_exprs, .. } => self.visit_exprs(array_exprs),
+            TableFactor::Pivot { table, aggregate_functions, value_column, value_source, default_on_null, .. } => {
+                self.visit_table_factor(table)?;
+                for function in aggregate_functions {
+                    self.visit_expr(&mut function.expr)?;
+                }
+                self.visit_exprs(value_column)?;
+                match value_source {
+                    sqlparser::ast::PivotValueSource::List(values) => {
+                        for value in values {
+                            self.visit_expr(&mut value.expr)?;
+                        }
+                    }
+                    sqlparser::ast::PivotValueSource::Any(order_by) => self.visit_order_by_exprs(order_by)?,
+                    sqlparser::ast::PivotValueSource::Subquery(query) => self.visit_query(query)?,
                 }
+                if let Some(expr) = default_on_null {
+                    self.visit_expr(expr)?;
+                }
+                Ok(())
+            }
+            TableFactor::Unpivot { table, .. } | TableFactor::MatchRecognize { table, .. } => {
+                self.visit_table_factor(table)
             }
+            _ => Ok(()),
         }
+    }
 
-        // 将条件合并并添加到查询中
-        if !conditions.is_empty() {
-            let combined_condition = if conditions.len() == 1 {
-                conditions.into_iter().next().unwrap()
-            } else {
-                sqlparser::ast::Expr::Nested(Box::new(conditions.into_iter().reduce(|left, right| {
-                    sqlparser::ast::Expr::BinaryOp {
-                        left: Box::new(left),
-                        op: sqlparser::ast::BinaryOperator::And,
-                        right: Box::new(right),
-                    }
-                }).unwrap()))
-            };
+    fn visit_order_by_exprs(&mut self, order_by: &mut [sqlparser::ast::OrderByExpr]) -> Result<(), DbErr> {
+        for order_by_expr in order_by {
+            self.visit_expr(&mut order_by_expr.expr)?;
+        }
+        Ok(())
+    }
 
-            // 添加到 WHERE 子句
-            if let Some(ref mut selection) = select.selection {
-                *selection = sqlparser::ast::Expr::BinaryOp {
-                    left: Box::new(std::mem::replace(selection, combined_condition.clone())),
-                    op: sqlparser::ast::BinaryOperator::And,
-                    right: Box::new(combined_condition),
-                };
-            } else {
-                select.selection = Some(combined_condition);
+    fn visit_exprs(&mut self, exprs: &mut [sqlparser::ast::Expr]) -> Result<(), DbErr> {
+        for expr in exprs {
+            self.visit_expr(expr)?;
+        }
+        Ok(())
+    }
+
+    fn visit_function_arguments(&mut self, arguments: &mut sqlparser::ast::FunctionArguments) -> Result<(), DbErr> {
+        match arguments {
+            sqlparser::ast::FunctionArguments::Subquery(query) => self.visit_query(query),
+            sqlparser::ast::FunctionArguments::List(list) => self.visit_function_args(&mut list.args),
+            sqlparser::ast::FunctionArguments::None => Ok(()),
+        }
+    }
+
+    fn visit_function_args(&mut self, args: &mut [sqlparser::ast::FunctionArg]) -> Result<(), DbErr> {
+        for arg in args {
+            let arg_expr = match arg {
+                sqlparser::ast::FunctionArg::Named { arg, .. }
+                | sqlparser::ast::FunctionArg::ExprNamed { arg, .. }
+                | sqlparser::ast::FunctionArg::Unnamed(arg) => arg,
+            };
+            if let sqlparser::ast::FunctionArgExpr::Expr(expr) = arg_expr {
+                self.visit_expr(expr)?;
             }
         }
-        
         Ok(())
     }
-}
 
-impl QueryHook for DefaultQueryHook {
-    fn before_query(&self, sql: &str) -> Result<Option<String>, DbErr> {
-        // 只处理 SELECT 语句
-        let sql_upper = sql.trim().to_uppercase();
-        if !sql_upper.starts_with("SELECT") {
-            return Ok(None);
+    fn visit_expr(&mut self, expr: &mut sqlparser::ast::Expr) -> Result<(), DbErr> {
+        use sqlparser::ast::Expr;
+
+        match expr {
+            // 子查询
+            Expr::Subquery(query) | Expr::Exists { subquery: query, .. } => self.visit_query(query),
+            Expr::InSubquery { expr, subquery, .. } => {
+                self.visit_expr(expr)?;
+                self.visit_query(subquery)
+            }
+            // 单个子表达式
+            Expr::IsFalse(expr)
+            | Expr::IsNotFalse(expr)
+            | Expr::IsTrue(expr)
+            | Expr::IsNotTrue(expr)
+            | Expr::IsNull(expr)
+            | Expr::IsNotNull(expr)
+            | Expr::IsUnknown(expr)
+            | Expr::IsNotUnknown(expr)
+            | Expr::IsNormalized { expr, .. }
+            | Expr::UnaryOp { expr, .. }
+            | Expr::Convert { expr, .. }
+            | Expr::Cast { expr, .. }
+            | Expr::Extract { expr, .. }
+            | Expr::Ceil { expr, .. }
+            | Expr::Floor { expr, .. }
+            | Expr::Collate { expr, .. }
+            | Expr::Nested(expr)
+            | Expr::Prefixed { value: expr, .. }
+            | Expr::Named { expr, .. }
+            | Expr::OuterJoin(expr)
+            | Expr::Prior(expr)
+            | Expr::CompoundFieldAccess { root: expr, .. }
+            | Expr::JsonAccess { value: expr, .. } => self.visit_expr(expr),
+            // 两个子表达式
+            Expr::IsDistinctFrom(left, right)
+            | Expr::IsNotDistinctFrom(left, right)
+            | Expr::BinaryOp { left, right, .. }
+            | Expr::AnyOp { left, right, .. }
+            | Expr::AllOp { left, right, .. }
+            | Expr::Like { expr: left, pattern: right, .. }
+            | Expr::ILike { expr: left, pattern: right, .. }
+            | Expr::SimilarTo { expr: left, pattern: right, .. }
+            | Expr::RLike { expr: left, pattern: right, .. }
+            | Expr::InUnnest { expr: left, array_expr: right, .. }
+            | Expr::AtTimeZone { timestamp: left, time_zone: right }
+            | Expr::Position { expr: left, r#in: right } => {
+                self.visit_expr(left)?;
+                self.visit_expr(right)
+            }
+            Expr::Between { expr, low, high, .. } => {
+                self.visit_expr(expr)?;
+                self.visit_expr(low)?;
+                self.visit_expr(high)
+            }
+            Expr::InList { expr, list, .. } => {
+                self.visit_expr(expr)?;
+                self.visit_exprs(list)
+            }
+            Expr::Substring { expr, substring_from, substring_for, .. } => {
+                self.visit_expr(expr)?;
+                for expr in [substring_from, substring_for].into_iter().flatten() {
+                    self.visit_expr(expr)?;
+                }
+                Ok(())
+            }
+            Expr::Trim { expr, trim_what, trim_characters, .. } => {
+                self.visit_expr(expr)?;
+                if let Some(expr) = trim_what {
+                    self.visit_expr(expr)?;
+                }
+                if let Some(exprs) = trim_characters {
+                    self.visit_exprs(exprs)?;
+                }
+                Ok(())
+            }
+            Expr::Overlay { expr, overlay_what, overlay_from, overlay_for } => {
+                self.visit_expr(expr)?;
+                self.visit_expr(overlay_what)?;
+                self.visit_expr(overlay_from)?;
+                if let Some(expr) = overlay_for {
+                    self.visit_expr(expr)?;
+                }
+                Ok(())
+            }
+            Expr::Case { operand, conditions, else_result, .. } => {
+                if let Some(expr) = operand {
+                    self.visit_expr(expr)?;
+                }
+                for case_when in conditions {
+                    self.visit_expr(&mut case_when.condition)?;
+                    self.visit_expr(&mut case_when.result)?;
+                }
+                if let Some(expr) = else_result {
+                    self.visit_expr(expr)?;
+                }
+                Ok(())
+            }
+            Expr::Function(function) => {
+                self.visit_function_arguments(&mut function.parameters)?;
+                self.visit_function_arguments(&mut function.args)?;
+                if let Some(expr) = &mut function.filter {
+                    self.visit_expr(expr)?;
+                }
+                Ok(())
+            }
+            Expr::Tuple(exprs) | Expr::Struct { values: exprs, .. } => self.visit_exprs(exprs),
+            Expr::GroupingSets(sets) | Expr::Cube(sets) | Expr::Rollup(sets) => {
+                for exprs in sets {
+                    self.visit_exprs(exprs)?;
+                }
+                Ok(())
+            }
+            Expr::Array(array) => self.visit_exprs(&mut array.elem),
+            _ => Ok(()),
         }
+    }
+}
 
-        // 解析并添加默认条件
-        match self.add_default_conditions(sql) {
-            Ok(modified_sql) => {
+impl QueryHook for DefaultQueryHook {
+    fn before_query(&self, sql: &str, backend: DatabaseBackend) -> Result<Option<String>, DbErr> {
+        // 解析并添加默认条件，语句类型由语法树判断
+        match self.add_default_conditions(sql, backend, &ParamBinder::inline(), &self.current_context()) {
+            Ok(Some(modified_sql)) => {
                 log::debug!("Modified SQL: {}", modified_sql);
                 if modified_sql != sql {
                     return Ok(Some(modified_sql));
                 }
             }
-            Err(e) => {
-                log::warn!("Failed to add default conditions to SQL: {}, error: {}", sql, e);
+            Ok(None) => {}
+            // 钩子主动拒绝的语句（如严格租户模式缺少租户、无法解析）返回错误，其余改写失败按解析失败策略处理
+            Err(e) => self.handle_rewrite_error(sql, backend, e)?,
+        }
+
+        Ok(None)
+    }
+
+    fn before_statement(&self, stmt: &Statement) -> Result<Option<Statement>, DbErr> {
+        // 保留原语句的绑定参数，租户ID等新增值以占位符形式绑定
+        match self.add_default_conditions_to_statement(stmt) {
+            Ok(Some(modified_stmt)) => {
+                log::debug!("Modified SQL: {}", modified_stmt.sql);
+                if modified_stmt.sql != stmt.sql {
+                    return Ok(Some(modified_stmt));
+                }
             }
+            Ok(None) => {}
+            Err(e) => self.handle_rewrite_error(&stmt.sql, stmt.db_backend, e)?,
         }
 
         Ok(None)
     }
 
-    fn after_query(&self, _sql: &str, _result: &Result<(), &DbErr>) {
+    fn after_query(&self, _outcome: &QueryOutcome) {
         // 移除调试日志，提高性能
     }
 }
@@ -330,7 +2315,9 @@ impl QueryHook for DefaultQueryHook {
 /// 带有查询钩子的连接包装器
 pub struct HookedConnection<C> {
     inner: C,
-    hook: Arc<dyn QueryHook>,
+    hook: Arc<dyn AsyncQueryHook>,
+    /// 通过此连接执行的语句关闭的过滤条件
+    disabled_filters: FilterKind,
 }
 
 impl<C: Clone> Clone for HookedConnection<C> {
@@ -338,6 +2325,7 @@ impl<C: Clone> Clone for HookedConnection<C> {
         Self {
             inner: self.inner.clone(),
             hook: Arc::clone(&self.hook),
+            disabled_filters: self.disabled_filters,
         }
     }
 }
@@ -345,12 +2333,113 @@ impl<C: Clone> Clone for HookedConnection<C> {
 impl<C> HookedConnection<C> {
     /// 创建新的钩子连接
     pub fn new(inner: C, hook: Arc<dyn QueryHook + 'static>) -> Self {
-        Self { inner, hook }
+        Self::new_async(inner, Arc::new(SyncHookAdapter::new(hook)))
+    }
+
+    /// 使用异步查询钩子创建新的钩子连接
+    pub fn new_async(inner: C, hook: Arc<dyn AsyncQueryHook + 'static>) -> Self {
+        Self {
+            inner,
+            hook,
+            disabled_filters: FilterKind::None,
+        }
     }
     
-    /// 从内部连接和全局钩子创建新的钩子连接
+    /// 从内部连接和全局钩子创建新的钩子连接，优先使用全局异步钩子
     pub fn new_with_global_hook(inner: C) -> Option<Self> {
-        get_extract_hook().map(|hook| Self { inner, hook })
+        match get_async_extract_hook() {
+            Some(hook) => Some(Self::new_async(inner, hook)),
+            None => get_extract_hook().map(|hook| Self::new(inner, hook)),
+        }
+    }
+
+    /// 创建关闭全部过滤条件的连接视图，用于管理及迁移代码，创建时及每条语句执行前记录审计日志
+    #[track_caller]
+    pub fn unfiltered(&self) -> Self
+    where
+        C: Clone,
+    {
+        self.with_filters_disabled(FilterKind::All)
+    }
+
+    /// 创建关闭指定过滤条件的连接视图，与原连接共享内部连接和钩子，创建时及每条语句执行前记录审计日志
+    #[track_caller]
+    pub fn with_filters_disabled(&self, kind: FilterKind) -> Self
+    where
+        C: Clone,
+    {
+        crate::filter_scope::audit_filter_bypass(kind, std::panic::Location::caller());
+        Self {
+            inner: self.inner.clone(),
+            hook: Arc::clone(&self.hook),
+            disabled_filters: self.disabled_filters.union(kind),
+        }
+    }
+
+    /// 关闭了过滤条件时记录即将执行的语句，派生出的事务同样记录
+    fn audit_statement(&self, sql: &str) {
+        if self.disabled_filters != FilterKind::None {
+            crate::filter_scope::audit_unfiltered_statement(self.disabled_filters, sql);
+        }
+    }
+
+    /// 由当前连接派生出的连接（如事务），继承钩子及关闭的过滤条件
+    fn derive<T>(&self, inner: T) -> HookedConnection<T> {
+        HookedConnection {
+            inner,
+            hook: Arc::clone(&self.hook),
+            disabled_filters: self.disabled_filters,
+        }
+    }
+}
+
+impl<C> HookedConnection<C>
+where
+    C: ConnectionTrait + Send + Sync + 'static,
+{
+    /// 调用钩子改写语句，返回待执行的语句及改写后的 SQL
+    async fn rewrite_statement(&self, stmt: Statement) -> Result<(Statement, Option<String>), DbErr> {
+        self.audit_statement(&stmt.sql);
+        match scope_disabled_filters(self.disabled_filters, self.hook.before_statement(&stmt)).await? {
+            Some(modified_stmt) => {
+                log::debug!("Modified SQL: {}", modified_stmt.sql);
+                let modified_sql = modified_stmt.sql.clone();
+                Ok((modified_stmt, Some(modified_sql)))
+            }
+            None => Ok((stmt, None)),
+        }
+    }
+
+    /// 执行语句并计时，执行后将结果交给钩子的 `after_query`
+    ///
+    /// `counts` 从执行结果中提取影响的行数与返回的行数
+    async fn run_with_hook<T, Fut>(
+        &self,
+        original_sql: &str,
+        rewritten_sql: Option<&str>,
+        future: Fut,
+        counts: fn(&T) -> (Option<u64>, Option<usize>),
+    ) -> Result<T, DbErr>
+    where
+        Fut: Future<Output = Result<T, DbErr>> + Send,
+    {
+        let start = Instant::now();
+        let result = future.await;
+        let elapsed = start.elapsed();
+
+        let (rows_affected, rows_returned) = result.as_ref().map_or((None, None), counts);
+        let outcome = QueryOutcome {
+            original_sql,
+            rewritten_sql,
+            elapsed,
+            rows_affected,
+            rows_returned,
+            backend: self.get_database_backend(),
+            context: AutoFieldContext::current_safe(),
+            result: result.as_ref().map(|_| ()),
+        };
+        self.hook.after_query(&outcome).await;
+        result
     }
 }
 
@@ -364,70 +2453,46 @@ where
     }
 
     async fn execute(&self, stmt: Statement) -> Result<ExecResult, DbErr> {
-        let sql = stmt.to_string();
-        log::debug!("Executing SQL: {}", sql);
-        if let Some(modified_sql) = self.hook.before_query(&sql)? {
-            log::debug!("Modified SQL: {}", modified_sql);
-            let modified_stmt = Statement::from_string(
-                self.get_database_backend(),
-                &modified_sql,
-            );
-            let result = self.inner.execute(modified_stmt).await;
-            self.hook.after_query(&modified_sql, &result.as_ref().map(|_| ()));
-            result
-        } else {
-            let result = self.inner.execute(stmt).await;
-            self.hook.after_query(&sql, &result.as_ref().map(|_| ()));
-            result
-        }
+        log::debug!("Executing SQL: {}", stmt.sql);
+        let original_sql = stmt.sql.clone();
+        let (stmt, rewritten_sql) = self.rewrite_statement(stmt).await?;
+        self.run_with_hook(&original_sql, rewritten_sql.as_deref(), self.inner.execute(stmt), |result| {
+            (Some(result.rows_affected()), None)
+        })
+        .await
     }
 
     async fn execute_unprepared(&self, sql: &str) -> Result<ExecResult, DbErr> {
         log::debug!("Executing unprepared SQL: {}", sql);
-        if let Some(modified_sql) = self.hook.before_query(sql)? {
+        self.audit_statement(sql);
+        let rewritten_sql =
+            scope_disabled_filters(self.disabled_filters, self.hook.before_query(sql, self.get_database_backend())).await?;
+        if let Some(modified_sql) = &rewritten_sql {
             log::debug!("Modified SQL: {}", modified_sql);
-            let result = self.inner.execute_unprepared(&modified_sql).await;
-            self.hook.after_query(&modified_sql, &result.as_ref().map(|_| ()));
-            result
-        } else {
-            let result = self.inner.execute_unprepared(sql).await;
-            self.hook.after_query(sql, &result.as_ref().map(|_| ()));
-            result
         }
+        let final_sql = rewritten_sql.as_deref().unwrap_or(sql);
+        self.run_with_hook(sql, rewritten_sql.as_deref(), self.inner.execute_unprepared(final_sql), |result| {
+            (Some(result.rows_affected()), None)
+        })
+        .await
     }
 
     async fn query_one(&self, stmt: Statement) -> Result<Option<QueryResult>, DbErr> {
-        let sql = stmt.to_string();
-        if let Some(modified_sql) = self.hook.before_query(&sql)? {
-            let modified_stmt = Statement::from_string(
-                self.get_database_backend(),
-                &modified_sql,
-            );
-            let result = self.inner.query_one(modified_stmt).await;
-            self.hook.after_query(&modified_sql, &result.as_ref().map(|_| ()));
-            result
-        } else {
-            let result = self.inner.query_one(stmt).await;
-            self.hook.after_query(&sql, &result.as_ref().map(|_| ()));
-            result
-        }
+        let original_sql = stmt.sql.clone();
+        let (stmt, rewritten_sql) = self.rewrite_statement(stmt).await?;
+        self.run_with_hook(&original_sql, rewritten_sql.as_deref(), self.inner.query_one(stmt), |result| {
+            (None, Some(usize::from(result.is_some())))
+        })
+        .await
     }
 
     async fn query_all(&self, stmt: Statement) -> Result<Vec<QueryResult>, DbErr> {
-        let sql = stmt.to_string();
-        if let Some(modified_sql) = self.hook.before_query(&sql)? {
-            let modified_stmt = Statement::from_string(
-                self.get_database_backend(),
-                &modified_sql,
-            );
-            let result = self.inner.query_all(modified_stmt).await;
-            self.hook.after_query(&modified_sql, &result.as_ref().map(|_| ()));
-            result
-        } else {
-            let result = self.inner.query_all(stmt).await;
-            self.hook.after_query(&sql, &result.as_ref().map(|_| ()));
-            result
-        }
+        let original_sql = stmt.sql.clone();
+        let (stmt, rewritten_sql) = self.rewrite_statement(stmt).await?;
+        self.run_with_hook(&original_sql, rewritten_sql.as_deref(), self.inner.query_all(stmt), |result| {
+            (None, Some(result.len()))
+        })
+        .await
     }
 
     fn support_returning(&self) -> bool {
@@ -439,6 +2504,116 @@ where
     }
 }
 
+/// 流式查询支持，查询前执行与其他查询相同的改写
+///
+/// 流的行数与耗时在读取完成前无法得知，因此流式查询不调用 `after_query`
+impl<C> StreamTrait for HookedConnection<C>
+where
+    C: StreamTrait + ConnectionTrait + Send + Sync + 'static,
+{
+    type Stream<'a>
+        = C::Stream<'a>
+    where
+        Self: 'a;
+
+    fn stream<'a>(&'a self, stmt: Statement) -> Pin<Box<dyn Future<Output = Result<Self::Stream<'a>, DbErr>> + 'a + Send>> {
+        Box::pin(async move {
+            log::debug!("Streaming SQL: {}", stmt.sql);
+            let (stmt, _) = self.rewrite_statement(stmt).await?;
+            self.inner.stream(stmt).await
+        })
+    }
+}
+
+/// 带有查询钩子的事务，与创建它的连接共享同一个钩子
+pub type HookedTransaction = HookedConnection<DatabaseTransaction>;
+
+/// 事务支持
+///
+/// sea-orm 的 `TransactionTrait` 只能返回原始的 `DatabaseTransaction`，在其上执行的语句会绕过钩子，
+/// 因此这里提供同名方法，返回共享钩子的 `HookedTransaction`；在事务上再次调用 `begin` 会创建保存点
+impl<C> HookedConnection<C>
+where
+    C: TransactionTrait + Send + Sync,
+{
+    /// 开启事务
+    pub async fn begin(&self) -> Result<HookedTransaction, DbErr> {
+        let transaction = self.inner.begin().await?;
+        Ok(self.derive(transaction))
+    }
+
+    /// 按指定的隔离级别及访问模式开启事务
+    pub async fn begin_with_config(
+        &self,
+        isolation_level: Option<IsolationLevel>,
+        access_mode: Option<AccessMode>,
+    ) -> Result<HookedTransaction, DbErr> {
+        let transaction = self.inner.begin_with_config(isolation_level, access_mode).await?;
+        Ok(self.derive(transaction))
+    }
+
+    /// 在事务中执行回调，回调返回错误时回滚，否则提交
+    pub async fn transaction<F, T, E>(&self, callback: F) -> Result<T, TransactionError<E>>
+    where
+        F: for<'c> FnOnce(&'c HookedTransaction) -> Pin<Box<dyn Future<Output = Result<T, E>> + Send + 'c>> + Send,
+        T: Send,
+        E: std::fmt::Display + std::fmt::Debug + Send,
+    {
+        let transaction = self.begin().await.map_err(TransactionError::Connection)?;
+        transaction.run(callback).await
+    }
+
+    /// 按指定的隔离级别及访问模式在事务中执行回调，回调返回错误时回滚，否则提交
+    pub async fn transaction_with_config<F, T, E>(
+        &self,
+        callback: F,
+        isolation_level: Option<IsolationLevel>,
+        access_mode: Option<AccessMode>,
+    ) -> Result<T, TransactionError<E>>
+    where
+        F: for<'c> FnOnce(&'c HookedTransaction) -> Pin<Box<dyn Future<Output = Result<T, E>> + Send + 'c>> + Send,
+        T: Send,
+        E: std::fmt::Display + std::fmt::Debug + Send,
+    {
+        let transaction = self
+            .begin_with_config(isolation_level, access_mode)
+            .await
+            .map_err(TransactionError::Connection)?;
+        transaction.run(callback).await
+    }
+}
+
+impl HookedTransaction {
+    /// 提交事务
+    pub async fn commit(self) -> Result<(), DbErr> {
+        self.inner.commit().await
+    }
+
+    /// 回滚事务
+    pub async fn rollback(self) -> Result<(), DbErr> {
+        self.inner.rollback().await
+    }
+
+    /// 执行回调并根据结果提交或回滚
+    async fn run<F, T, E>(self, callback: F) -> Result<T, TransactionError<E>>
+    where
+        F: for<'c> FnOnce(&'c HookedTransaction) -> Pin<Box<dyn Future<Output = Result<T, E>> + Send + 'c>> + Send,
+        T: Send,
+        E: std::fmt::Display + std::fmt::Debug + Send,
+    {
+        match callback(&self).await {
+            Ok(result) => {
+                self.commit().await.map_err(TransactionError::Connection)?;
+                Ok(result)
+            }
+            Err(e) => {
+                self.rollback().await.map_err(TransactionError::Connection)?;
+                Err(TransactionError::Transaction(e))
+            }
+        }
+    }
+}
+
 /// 全局查询钩子注册表
 static EXTRACT_HOOK_REGISTRY: parking_lot::RwLock<Option<Arc<dyn QueryHook>>> = parking_lot::RwLock::new(None);
 
@@ -458,4 +2633,45 @@ pub fn get_extract_hook() -> Option<Arc<dyn QueryHook>> {
 pub fn unregister_extract_hook() {
     let mut registry = EXTRACT_HOOK_REGISTRY.write();
     *registry = None;
-}
\ No newline at end of file
+}
+/// 全局异步查询钩子注册表
+static ASYNC_EXTRACT_HOOK_REGISTRY: parking_lot::RwLock<Option<Arc<dyn AsyncQueryHook>>> = parking_lot::RwLock::new(None);
+
+/// 注册全局异步查询钩子，插件在这里注册钩子链
+pub fn register_async_extract_hook(hook: Arc<dyn AsyncQueryHook>) {
+    let mut registry = ASYNC_EXTRACT_HOOK_REGISTRY.write();
+    *registry = Some(hook);
+}
+
+/// 获取全局异步查询钩子
+pub fn get_async_extract_hook() -> Option<Arc<dyn AsyncQueryHook>> {
+    let registry = ASYNC_EXTRACT_HOOK_REGISTRY.read();
+    registry.clone()
+}
+
+/// 移除全局异步查询钩子
+pub fn unregister_async_extract_hook() {
+    let mut registry = ASYNC_EXTRACT_HOOK_REGISTRY.write();
+    *registry = None;
+}
+
+/// 全局默认查询钩子注册表，插件之外可通过它登记表结构、读取缓存统计及解析失败次数
+static DEFAULT_QUERY_HOOK_REGISTRY: parking_lot::RwLock<Option<Arc<DefaultQueryHook>>> = parking_lot::RwLock::new(None);
+
+/// 注册全局默认查询钩子
+pub fn register_default_query_hook(hook: Arc<DefaultQueryHook>) {
+    let mut registry = DEFAULT_QUERY_HOOK_REGISTRY.write();
+    *registry = Some(hook);
+}
+
+/// 获取全局默认查询钩子
+pub fn get_default_query_hook() -> Option<Arc<DefaultQueryHook>> {
+    let registry = DEFAULT_QUERY_HOOK_REGISTRY.read();
+    registry.clone()
+}
+
+/// 移除全局默认查询钩子
+pub fn unregister_default_query_hook() {
+    let mut registry = DEFAULT_QUERY_HOOK_REGISTRY.write();
+    *registry = None;
+}
@@ -0,0 +1,149 @@
+//! 任务级的过滤控制
+//!
+//! 通过 tokio 的 task_local 在单个 Future 范围内调整钩子行为，不影响其他并发请求
+
+use std::future::Future;
+
+tokio::task_local! {
+    /// 当前任务是否执行物理删除
+    static HARD_DELETE: bool;
+}
+
+/// 在给定的 Future 中执行物理删除
+///
+/// 开启 DELETE 转软删除后，作用域内的 DELETE 语句不会被改写为 UPDATE
+pub async fn hard_delete<F: Future>(future: F) -> F::Output {
+    HARD_DELETE.scope(true, future).await
+}
+
+/// 当前任务是否处于物理删除作用域中
+pub fn is_hard_delete() -> bool {
+    HARD_DELETE.try_with(|hard_delete| *hard_delete).unwrap_or(false)
+}
+
+tokio::task_local! {
+    /// 当前任务中关闭的过滤条件
+    static DISABLED_FILTERS: FilterKind;
+}
+
+/// 可按任务关闭的过滤条件，多个过滤条件可通过 `union` 组合
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct FilterKind(u8);
+
+#[allow(non_upper_case_globals)]
+impl FilterKind {
+    /// 软删除过滤
+    pub const SoftDelete: FilterKind = FilterKind(0b001);
+    /// 租户过滤
+    pub const Tenant: FilterKind = FilterKind(0b010);
+    /// 数据权限过滤
+    pub const DataScope: FilterKind = FilterKind(0b100);
+    /// 全部过滤
+    pub const All: FilterKind = FilterKind(0b111);
+    /// 不关闭任何过滤
+    pub const None: FilterKind = FilterKind(0b000);
+
+    /// 合并两组关闭的过滤条件
+    pub fn union(self, other: FilterKind) -> FilterKind {
+        FilterKind(self.0 | other.0)
+    }
+
+    /// 是否包含指定的过滤条件
+    pub fn contains(self, kind: FilterKind) -> bool {
+        self.0 & kind.0 == kind.0
+    }
+}
+
+impl std::fmt::Debug for FilterKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            FilterKind::All => return f.write_str("All"),
+            FilterKind::None => return f.write_str("None"),
+            _ => {}
+        }
+        let names: Vec<&str> = [
+            (FilterKind::SoftDelete, "SoftDelete"),
+            (FilterKind::Tenant, "Tenant"),
+            (FilterKind::DataScope, "DataScope"),
+        ]
+        .into_iter()
+        .filter(|(kind, _)| self.contains(*kind))
+        .map(|(_, name)| name)
+        .collect();
+        f.write_str(&names.join(" | "))
+    }
+}
+
+/// 在给定的 Future 中关闭指定的过滤条件，不影响其他并发请求
+///
+/// 嵌套使用时关闭的过滤条件取并集；每次调用都会以 `audit` 为 target 记录调用位置及当前用户、租户
+#[track_caller]
+pub fn without_filters<F: Future>(kind: FilterKind, future: F) -> impl Future<Output = F::Output> {
+    audit_filter_bypass(kind, std::panic::Location::caller());
+    DISABLED_FILTERS.scope(disabled_filters().union(kind), future)
+}
+
+/// 当前任务中关闭的过滤条件
+pub fn disabled_filters() -> FilterKind {
+    DISABLED_FILTERS.try_with(|kind| *kind).unwrap_or(FilterKind::None)
+}
+
+/// 当前任务中是否关闭了指定的过滤条件
+pub fn is_filter_disabled(kind: FilterKind) -> bool {
+    disabled_filters().contains(kind)
+}
+
+/// 在给定的 Future 中额外关闭指定的过滤条件，不记录审计日志，供已记录审计日志的调用方使用
+pub(crate) async fn scope_disabled_filters<F: Future>(kind: FilterKind, future: F) -> F::Output {
+    if kind == FilterKind::None {
+        return future.await;
+    }
+    DISABLED_FILTERS.scope(disabled_filters().union(kind), future).await
+}
+
+tokio::task_local! {
+    /// 当前任务是否以系统身份执行
+    static SYSTEM_SCOPE: bool;
+}
+
+/// 以系统身份执行给定的 Future，用于定时任务等没有租户的场景
+///
+/// 严格租户模式下，作用域内上下文没有租户ID的语句不再被拒绝；每次调用都会记录审计日志
+#[track_caller]
+pub fn system_scope<F: Future>(future: F) -> impl Future<Output = F::Output> {
+    audit("system scope", std::panic::Location::caller());
+    SYSTEM_SCOPE.scope(true, future)
+}
+
+/// 当前任务是否以系统身份执行
+pub fn is_system_scope() -> bool {
+    SYSTEM_SCOPE.try_with(|system| *system).unwrap_or(false)
+}
+
+/// 记录关闭过滤条件的审计日志
+pub(crate) fn audit_filter_bypass(kind: FilterKind, location: &std::panic::Location<'_>) {
+    audit(&format!("filters disabled: {:?}", kind), location);
+}
+
+/// 记录在关闭了过滤条件的连接上执行的语句
+pub(crate) fn audit_unfiltered_statement(kind: FilterKind, sql: &str) {
+    audit_with(&format!("statement with filters disabled: {:?}", kind), format_args!("sql: {}", sql));
+}
+
+/// 以 `audit` 为 target 记录审计日志，包含调用位置及当前用户、租户
+fn audit(action: &str, location: &std::panic::Location<'_>) {
+    audit_with(action, format_args!("at: {}", location));
+}
+
+/// 以 `audit` 为 target 记录审计日志，包含附加信息及当前用户、租户
+fn audit_with(action: &str, detail: std::fmt::Arguments<'_>) {
+    let context = crate::auto_field_trait::AutoFieldContext::current_safe();
+    log::warn!(
+        target: "audit",
+        "{}, {}, tenant: {}, user: {}",
+        action,
+        detail,
+        context.tenant_id.as_deref().unwrap_or("-"),
+        context.user_name.as_deref().or(context.user_id.as_deref()).unwrap_or("-"),
+    );
+}
@@ -0,0 +1,205 @@
+//! 可组合的查询钩子链
+//!
+//! 按优先级依次执行多个查询钩子：`before_query` 按优先级从小到大执行，
+//! 每个钩子接收上一个钩子改写后的 SQL；`after_query` 按相反顺序执行。
+//! 钩子链中可同时包含同步钩子与异步钩子，同步钩子以 `SyncHookAdapter` 适配。
+//! 钩子链也实现同步的 `QueryHook`，此时只能执行同步钩子，包含异步钩子时拒绝执行语句。
+
+use crate::error::HookError;
+use crate::extract_hook::{AsyncQueryHook, QueryHook, QueryOutcome, SyncHookAdapter};
+use parking_lot::RwLock;
+use sea_orm::{DatabaseBackend, DbErr, Statement};
+use std::sync::Arc;
+
+/// 默认查询钩子（软删除、租户过滤等）在钩子链中的优先级
+pub const DEFAULT_QUERY_HOOK_PRIORITY: i32 = 0;
+
+/// 慢查询日志钩子在钩子链中的优先级，最先执行 `before_query`、最后执行 `after_query`
+pub const SLOW_QUERY_HOOK_PRIORITY: i32 = i32::MIN;
+
+/// 钩子链中的钩子及其优先级
+#[derive(Clone)]
+struct PrioritizedHook {
+    priority: i32,
+
+    /// 添加时传入的钩子地址，同步钩子为适配前的地址，用于移除钩子
+    key: usize,
+
+    hook: Arc<dyn AsyncQueryHook>,
+
+    /// 适配前的同步钩子，异步钩子为 None
+    sync_hook: Option<Arc<dyn QueryHook>>,
+}
+
+/// 查询钩子链，本身也实现 `AsyncQueryHook` 及 `QueryHook`，克隆后共享同一组钩子
+#[derive(Clone, Default)]
+pub struct HookChain {
+    /// 按优先级从小到大排列的钩子，优先级相同时按添加顺序排列
+    hooks: Arc<RwLock<Vec<PrioritizedHook>>>,
+}
+
+impl HookChain {
+    /// 创建空的钩子链
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加同步钩子，优先级越小越先执行 `before_query`
+    pub fn with_hook(self, hook: Arc<dyn QueryHook>, priority: i32) -> Self {
+        self.add_hook(hook, priority);
+        self
+    }
+
+    /// 添加异步钩子，优先级越小越先执行 `before_query`
+    pub fn with_async_hook(self, hook: Arc<dyn AsyncQueryHook>, priority: i32) -> Self {
+        self.add_async_hook(hook, priority);
+        self
+    }
+
+    /// 添加同步钩子，优先级越小越先执行 `before_query`
+    pub fn add_hook(&self, hook: Arc<dyn QueryHook>, priority: i32) {
+        let key = hook_key(&hook);
+        self.insert(PrioritizedHook {
+            priority,
+            key,
+            hook: Arc::new(SyncHookAdapter::new(hook.clone())),
+            sync_hook: Some(hook),
+        });
+    }
+
+    /// 添加异步钩子，优先级越小越先执行 `before_query`
+    pub fn add_async_hook(&self, hook: Arc<dyn AsyncQueryHook>, priority: i32) {
+        self.insert(PrioritizedHook {
+            priority,
+            key: hook_key(&hook),
+            hook,
+            sync_hook: None,
+        });
+    }
+
+    /// 按优先级插入钩子，优先级相同时排在已有钩子之后
+    fn insert(&self, entry: PrioritizedHook) {
+        let mut hooks = self.hooks.write();
+        let index = hooks.partition_point(|existing| existing.priority <= entry.priority);
+        hooks.insert(index, entry);
+    }
+
+    /// 移除指定的同步钩子，返回是否移除成功
+    pub fn remove_hook(&self, hook: &Arc<dyn QueryHook>) -> bool {
+        self.remove(hook_key(hook))
+    }
+
+    /// 移除指定的异步钩子，返回是否移除成功
+    pub fn remove_async_hook(&self, hook: &Arc<dyn AsyncQueryHook>) -> bool {
+        self.remove(hook_key(hook))
+    }
+
+    fn remove(&self, key: usize) -> bool {
+        let mut hooks = self.hooks.write();
+        let len = hooks.len();
+        hooks.retain(|existing| existing.key != key);
+        hooks.len() != len
+    }
+
+    /// 钩子数量
+    pub fn len(&self) -> usize {
+        self.hooks.read().len()
+    }
+
+    /// 钩子链是否为空
+    pub fn is_empty(&self) -> bool {
+        self.hooks.read().is_empty()
+    }
+
+    /// 是否包含异步钩子，包含时不能以同步的 `QueryHook` 执行
+    pub fn has_async_hooks(&self) -> bool {
+        self.hooks.read().iter().any(|entry| entry.sync_hook.is_none())
+    }
+
+    /// 获取当前钩子的快照，避免执行钩子期间持有锁
+    fn snapshot(&self) -> Vec<Arc<dyn AsyncQueryHook>> {
+        self.hooks.read().iter().map(|entry| entry.hook.clone()).collect()
+    }
+
+    /// 获取当前同步钩子的快照，存在异步钩子时返回错误
+    fn sync_snapshot(&self) -> Result<Vec<Arc<dyn QueryHook>>, DbErr> {
+        self.hooks
+            .read()
+            .iter()
+            .map(|entry| {
+                entry
+                    .sync_hook
+                    .clone()
+                    .ok_or_else(|| HookError::AsyncHook { priority: entry.priority }.into())
+            })
+            .collect()
+    }
+}
+
+/// 钩子的地址，用于识别同一个钩子
+fn hook_key<T: ?Sized>(hook: &Arc<T>) -> usize {
+    Arc::as_ptr(hook) as *const () as usize
+}
+
+#[async_trait::async_trait]
+impl AsyncQueryHook for HookChain {
+    async fn before_query(&self, sql: &str, backend: DatabaseBackend) -> Result<Option<String>, DbErr> {
+        let mut modified: Option<String> = None;
+        for hook in self.snapshot() {
+            let current = modified.as_deref().unwrap_or(sql);
+            if let Some(rewritten) = hook.before_query(current, backend).await? {
+                modified = Some(rewritten);
+            }
+        }
+        Ok(modified)
+    }
+
+    async fn before_statement(&self, stmt: &Statement) -> Result<Option<Statement>, DbErr> {
+        let mut modified: Option<Statement> = None;
+        for hook in self.snapshot() {
+            let current = modified.as_ref().unwrap_or(stmt);
+            if let Some(rewritten) = hook.before_statement(current).await? {
+                modified = Some(rewritten);
+            }
+        }
+        Ok(modified)
+    }
+
+    async fn after_query(&self, outcome: &QueryOutcome<'_>) {
+        for hook in self.snapshot().iter().rev() {
+            hook.after_query(outcome).await;
+        }
+    }
+}
+
+impl QueryHook for HookChain {
+    fn before_query(&self, sql: &str, backend: DatabaseBackend) -> Result<Option<String>, DbErr> {
+        let mut modified: Option<String> = None;
+        for hook in self.sync_snapshot()? {
+            let current = modified.as_deref().unwrap_or(sql);
+            if let Some(rewritten) = hook.before_query(current, backend)? {
+                modified = Some(rewritten);
+            }
+        }
+        Ok(modified)
+    }
+
+    fn before_statement(&self, stmt: &Statement) -> Result<Option<Statement>, DbErr> {
+        let mut modified: Option<Statement> = None;
+        for hook in self.sync_snapshot()? {
+            let current = modified.as_ref().unwrap_or(stmt);
+            if let Some(rewritten) = hook.before_statement(current)? {
+                modified = Some(rewritten);
+            }
+        }
+        Ok(modified)
+    }
+
+    /// 只执行同步钩子，语句在 `before_query` 阶段已因异步钩子被拒绝时不会执行到这里
+    fn after_query(&self, outcome: &QueryOutcome) {
+        let hooks: Vec<Arc<dyn QueryHook>> = self.hooks.read().iter().filter_map(|entry| entry.sync_hook.clone()).collect();
+        for hook in hooks.iter().rev() {
+            hook.after_query(outcome);
+        }
+    }
+}
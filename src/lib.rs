@@ -1,12 +1,23 @@
 /// 自动字段处理库
 pub mod auto_field_trait;
+pub mod clock;
 pub mod extract_hook;
 pub mod config;
+pub mod error;
+pub mod filter_scope;
+pub mod hook_chain;
 pub mod pagination;
+pub mod rewrite_cache;
+pub mod schema_registry;
+pub mod slow_query_hook;
+pub mod tenant_hierarchy;
 
 use anyhow::Context;
-use config::SeaOrmConfig;
-use extract_hook::{register_extract_hook, DefaultQueryHook, HookedConnection};
+use config::{SeaOrmConfig, TenantHierarchyMode};
+use extract_hook::{
+    register_async_extract_hook, register_default_query_hook, register_extract_hook, AsyncQueryHook, DefaultQueryHook,
+    HookedConnection, QueryHook, SyncHookAdapter,
+};
 use sea_orm::{ConnectOptions, Database};
 use spring::async_trait;
 use spring::config::ConfigRegistry;
@@ -18,8 +29,21 @@ use std::time::Duration;
 /// 数据库连接类型别名
 pub type DbConn = HookedConnection<sea_orm::DbConn>;
 
+/// 数据库事务类型别名
+pub type DbTxn = extract_hook::HookedTransaction;
+
 /// 数据库连接钩子插件，用于初始化并包装数据库连接为HookedConnection
-pub struct HookedSeaOrmPlugin;
+///
+/// 默认查询钩子以 `DEFAULT_QUERY_HOOK_PRIORITY` 加入钩子链，可通过 `with_hook`、`with_async_hook` 添加其他钩子；
+/// 默认查询钩子同时以 `Arc<DefaultQueryHook>` 注册为组件，也可通过 `get_default_query_hook` 获取
+#[derive(Clone, Default)]
+pub struct HookedSeaOrmPlugin {
+    /// 额外的查询钩子及其优先级，同步钩子以 `SyncHookAdapter` 适配
+    hooks: Vec<(Arc<dyn AsyncQueryHook>, i32)>,
+
+    /// 租户层级提供者，优先于配置文件中的租户层级配置
+    tenant_hierarchy: Option<Arc<dyn TenantHierarchyProvider>>,
+}
 
 #[async_trait]
 impl Plugin for HookedSeaOrmPlugin {
@@ -32,33 +56,143 @@ impl Plugin for HookedSeaOrmPlugin {
         let conn = Self::connect(&config)
             .await
             .expect("sea-orm plugin load failed");
-        let soft_delete = match config.enable_soft_delete {
-            Some(true) => true,
-            _ => false
-        };
-        let tenant_filter = match config.enable_tenant_filter {
-            Some(true) => true,
-            _ => false
-        };
+        let soft_delete = matches!(config.enable_soft_delete, Some(true));
+        let tenant_filter = matches!(config.enable_tenant_filter, Some(true));
+        let update_filter = matches!(config.enable_update_filter, Some(true));
+        let delete_filter = matches!(config.enable_delete_filter, Some(true));
+        let soft_delete_rewrite = matches!(config.enable_soft_delete_rewrite, Some(true));
+        let insert_fill = matches!(config.enable_insert_fill, Some(true));
+        let update_fill = matches!(config.enable_update_fill, Some(true));
+        let strict_tenant = matches!(config.enable_strict_tenant, Some(true));
+        let data_scope = matches!(config.enable_data_scope, Some(true));
         // 创建并注册默认查询钩子
-        let default_hook = Arc::new(DefaultQueryHook::new(soft_delete,tenant_filter));
+        let mut default_hook = DefaultQueryHook::new(soft_delete, tenant_filter)
+            .with_update_filter(update_filter)
+            .with_delete_filter(delete_filter)
+            .with_soft_delete_rewrite(soft_delete_rewrite)
+            .with_insert_fill(insert_fill)
+            .with_update_fill(update_fill)
+            .with_strict_tenant(strict_tenant)
+            .with_data_scope(data_scope)
+            .with_parse_failure_policy(config.on_parse_failure.unwrap_or_default())
+            .with_rewrite_cache_size(config.rewrite_cache_size.unwrap_or(DEFAULT_REWRITE_CACHE_SIZE));
+        if let Some(tenant_hierarchy) = self.tenant_hierarchy(&config, &conn).await {
+            default_hook = default_hook.with_tenant_hierarchy(tenant_hierarchy);
+        }
+        let default_hook = Arc::new(default_hook);
         if let Some(tables) = &config.skip_table {
             for table in tables {
                 log::info!("skip table:{}", table);
                 default_hook.add_skip_table(table);
             }
         }
-        register_extract_hook(default_hook.clone());
+        if let Some(filter_columns) = &config.filter_columns {
+            default_hook.set_filter_columns(filter_columns.clone());
+        }
+        if let Some(tables) = &config.table_filter_columns {
+            for (table, filter_columns) in tables {
+                log::info!("filter columns of table:{}", table);
+                default_hook.set_table_filter_columns(table, filter_columns.clone());
+            }
+        }
+        if let Some(tables) = &config.version_table {
+            for table in tables {
+                log::info!("version table:{}", table);
+                default_hook.add_version_table(table);
+            }
+        }
+        if matches!(config.enable_schema_introspection, Some(true)) {
+            match default_hook.schema_registry().load_from_database(&conn).await {
+                Ok(count) => log::info!("schema introspection loaded {} tables", count),
+                Err(e) => log::warn!("schema introspection failed: {}", e),
+            }
+        }
+        // 默认钩子与额外添加的钩子组成钩子链
+        let hook_chain = HookChain::new().with_hook(default_hook.clone(), DEFAULT_QUERY_HOOK_PRIORITY);
+        for (hook, priority) in &self.hooks {
+            hook_chain.add_async_hook(hook.clone(), *priority);
+        }
+        if let Some(threshold_ms) = config.slow_query_threshold_ms {
+            log::info!("slow query threshold:{} ms", threshold_ms);
+            hook_chain.add_hook(Arc::new(SlowQueryHook::from_millis(threshold_ms)), SLOW_QUERY_HOOK_PRIORITY);
+        }
+        log::info!("query hook chain:{} hooks", hook_chain.len());
+        // 同步注册表中的钩子不能执行异步钩子，钩子链包含异步钩子时只注册默认钩子
+        if hook_chain.has_async_hooks() {
+            register_extract_hook(default_hook.clone());
+        } else {
+            register_extract_hook(Arc::new(hook_chain.clone()));
+        }
+        let hook_chain: Arc<dyn AsyncQueryHook> = Arc::new(hook_chain);
+        register_async_extract_hook(hook_chain.clone());
+        register_default_query_hook(default_hook.clone());
         // 将原始连接包装为HookedConnection
-        let hooked_conn = HookedConnection::new(conn.clone(), default_hook);
+        let hooked_conn = HookedConnection::new_async(conn.clone(), hook_chain);
         
-        // 同时注册原始连接和HookedConnection到组件注册表中
+        // 同时注册原始连接、HookedConnection及默认查询钩子到组件注册表中
         app.add_component(conn)
-            .add_component(hooked_conn);
+            .add_component(hooked_conn)
+            .add_component(default_hook);
     }
 }
 
 impl HookedSeaOrmPlugin {
+    /// 创建插件
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加查询钩子，优先级越小越先执行 `before_query`
+    pub fn with_hook(mut self, hook: Arc<dyn QueryHook>, priority: i32) -> Self {
+        self.hooks.push((Arc::new(SyncHookAdapter::new(hook)), priority));
+        self
+    }
+
+    /// 添加异步查询钩子（如改写前需要查询数据库或缓存），优先级越小越先执行 `before_query`
+    pub fn with_async_hook(mut self, hook: Arc<dyn AsyncQueryHook>, priority: i32) -> Self {
+        self.hooks.push((hook, priority));
+        self
+    }
+
+    /// 设置租户层级提供者，上级租户可见下级租户的数据
+    pub fn with_tenant_hierarchy(mut self, provider: Arc<dyn TenantHierarchyProvider>) -> Self {
+        self.tenant_hierarchy = Some(provider);
+        self
+    }
+
+    /// 获取租户层级提供者，未通过 `with_tenant_hierarchy` 设置时按配置创建，闭包表在这里读取并缓存
+    async fn tenant_hierarchy(
+        &self,
+        config: &SeaOrmConfig,
+        conn: &sea_orm::DbConn,
+    ) -> Option<Arc<dyn TenantHierarchyProvider>> {
+        if let Some(provider) = &self.tenant_hierarchy {
+            return Some(provider.clone());
+        }
+        let hierarchy = config.tenant_hierarchy.as_ref()?;
+        match hierarchy.mode {
+            TenantHierarchyMode::ClosureTable => {
+                let closure_table = ClosureTableHierarchy::new();
+                match closure_table
+                    .load_from_database(conn, &hierarchy.table, &hierarchy.parent_column, &hierarchy.id_column)
+                    .await
+                {
+                    Ok(count) => log::info!("tenant hierarchy loaded {} tenants from {}", count, hierarchy.table),
+                    Err(e) => log::warn!("tenant hierarchy load failed: {}", e),
+                }
+                Some(Arc::new(closure_table))
+            }
+            TenantHierarchyMode::RecursiveCte => {
+                log::info!("tenant hierarchy:recursive cte on {}", hierarchy.table);
+                Some(Arc::new(RecursiveCteHierarchy::new(
+                    &hierarchy.table,
+                    &hierarchy.id_column,
+                    &hierarchy.parent_column,
+                )))
+            }
+        }
+    }
+
     /// 连接数据库
     pub async fn connect(config: &SeaOrmConfig) -> Result<sea_orm::DbConn> {
         let mut opt = ConnectOptions::new(&config.uri);
@@ -86,5 +220,17 @@ impl HookedSeaOrmPlugin {
 
 
 // 重新导出核心类型和宏，方便用户使用
-pub use auto_field_trait::{register_context_getter, AutoFieldContext, ContextInfoProvider, QueryExtensions, CustomizationExt};
+pub use auto_field_trait::{
+    deleted_value_expr, not_deleted_condition, register_context_getter, soft_delete_column, soft_delete_strategy,
+    AutoFieldContext, ContextInfoProvider, CustomizationExt, DataScope, QueryExtensions,
+};
+pub use clock::{register_clock, Clock};
+pub use config::{ParseFailurePolicy, SoftDeleteStrategy};
+pub use error::{HookError, HookErrorKind};
+pub use filter_scope::{hard_delete, system_scope, without_filters, FilterKind};
+pub use hook_chain::{HookChain, DEFAULT_QUERY_HOOK_PRIORITY, SLOW_QUERY_HOOK_PRIORITY};
 pub use pagination::{Page, PageResult, Pagination, PaginationExt};
+pub use rewrite_cache::{RewriteCacheStats, DEFAULT_REWRITE_CACHE_SIZE};
+pub use schema_registry::SchemaRegistry;
+pub use slow_query_hook::SlowQueryHook;
+pub use tenant_hierarchy::{ClosureTableHierarchy, RecursiveCteHierarchy, TenantHierarchyProvider};
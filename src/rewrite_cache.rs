@@ -0,0 +1,215 @@
+//! SQL 改写结果缓存
+//!
+//! sea-orm 生成的语句结构高度重复，缓存以 SQL 文本及过滤配置为键，保存改写后的模板：
+//! 模板中的租户ID、用户等新增值以占位符表示，命中时直接用当前上下文生成参数，跳过解析与序列化。
+
+use crate::auto_field_trait::{AutoFieldContext, DataScope};
+use hashlink::LruCache;
+use parking_lot::Mutex;
+use sea_orm::prelude::DateTime;
+use sea_orm::{DatabaseBackend, DbErr, Statement};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// 默认的缓存容量
+pub const DEFAULT_REWRITE_CACHE_SIZE: usize = 1024;
+
+/// 模板中由上下文提供的值
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ContextValue {
+    TenantId,
+    TenantName,
+    UserId,
+    UserName,
+    /// 可见租户ID中的第 n 个（从 0 开始）
+    VisibleTenant(usize),
+    /// 数据权限范围内可见部门ID中的第 n 个（从 0 开始）
+    ScopeDept(usize),
+    /// 全局时钟的当前时间
+    CurrentTime,
+}
+
+impl ContextValue {
+    /// 从上下文中获取对应的值，当前时间取 `now`
+    fn resolve(self, context: &AutoFieldContext, now: Option<DateTime>) -> Option<sea_orm::Value> {
+        let value = match self {
+            ContextValue::TenantId => context.tenant_id.clone(),
+            ContextValue::TenantName => context.tenant_name.clone(),
+            ContextValue::UserId => context.user_id.clone(),
+            ContextValue::UserName => context.user_name.clone(),
+            ContextValue::VisibleTenant(index) => context.visible_tenant_ids().into_iter().nth(index),
+            ContextValue::ScopeDept(index) => context.data_scope_dept_ids().into_iter().nth(index),
+            ContextValue::CurrentTime => return now.map(sea_orm::Value::from),
+        };
+        value.map(sea_orm::Value::from)
+    }
+}
+
+/// 模板中参数的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TemplateValue {
+    /// 原语句中的第 n 个参数（从 0 开始）
+    Original(usize),
+    /// 由上下文提供的值
+    Context(ContextValue),
+}
+
+/// 改写模板
+#[derive(Debug, Clone)]
+pub(crate) struct RewriteTemplate {
+    /// 改写后的 SQL，新增值均为占位符
+    pub(crate) sql: String,
+
+    /// 按占位符顺序排列的参数来源
+    pub(crate) values: Vec<TemplateValue>,
+}
+
+impl RewriteTemplate {
+    /// 使用原语句的参数与当前上下文生成改写后的语句
+    ///
+    /// 当前时间只读取一次，同一语句中的多行及多个时间字段取值相同
+    pub(crate) fn instantiate(&self, stmt: &Statement, context: &AutoFieldContext) -> Result<Statement, DbErr> {
+        let original = stmt.values.as_ref().map(|values| values.0.as_slice()).unwrap_or_default();
+        let now = self
+            .values
+            .contains(&TemplateValue::Context(ContextValue::CurrentTime))
+            .then(crate::clock::current_time);
+        let values = self
+            .values
+            .iter()
+            .map(|value| match value {
+                TemplateValue::Original(index) => original.get(*index).cloned(),
+                TemplateValue::Context(source) => source.resolve(context, now),
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| DbErr::Custom(format!("Missing parameter for rewritten SQL: {}", self.sql)))?;
+        Ok(Statement::from_sql_and_values(stmt.db_backend, self.sql.clone(), values))
+    }
+}
+
+/// 缓存键：SQL 文本、数据库类型、过滤配置及影响改写结构的上下文状态
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct RewriteCacheKey {
+    sql: String,
+    backend: u8,
+    config: u64,
+    config_generation: u64,
+    context: [u8; 4],
+    visible_tenants: usize,
+    data_scope: u8,
+    scope_depts: usize,
+}
+
+impl RewriteCacheKey {
+    /// 创建缓存键
+    ///
+    /// `config` 为影响改写结果的配置摘要，`config_generation` 为表级配置的版本；上下文只记录各字段是否存在、是否为空、可见租户的数量及数据权限范围，
+    /// 因为这些状态决定了是否添加租户及数据权限条件、IN 列表的长度及填充字段，具体取值在命中时再从上下文获取
+    pub(crate) fn new(
+        sql: &str,
+        backend: DatabaseBackend,
+        config: u64,
+        config_generation: u64,
+        context: &AutoFieldContext,
+    ) -> Self {
+        let state = |value: &Option<String>| match value {
+            None => 0,
+            Some(value) if value.is_empty() => 1,
+            Some(_) => 2,
+        };
+        Self {
+            sql: sql.to_string(),
+            backend: match backend {
+                DatabaseBackend::MySql => 0,
+                DatabaseBackend::Postgres => 1,
+                DatabaseBackend::Sqlite => 2,
+            },
+            config,
+            config_generation,
+            context: [
+                state(&context.tenant_id),
+                state(&context.tenant_name),
+                state(&context.user_id),
+                state(&context.user_name),
+            ],
+            visible_tenants: context.visible_tenant_ids().len(),
+            data_scope: match context.data_scope {
+                DataScope::All => 0,
+                DataScope::Dept => 1,
+                DataScope::DeptAndSub(_) => 2,
+                DataScope::Own => 3,
+                DataScope::Custom(_) => 4,
+            },
+            scope_depts: context.data_scope_dept_ids().len(),
+        }
+    }
+}
+
+/// 缓存统计信息
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RewriteCacheStats {
+    /// 命中次数
+    pub hits: u64,
+
+    /// 未命中次数
+    pub misses: u64,
+
+    /// 当前缓存条目数
+    pub size: usize,
+
+    /// 缓存容量
+    pub capacity: usize,
+}
+
+/// 有界 LRU 改写缓存，容量为 0 时不缓存
+pub(crate) struct RewriteCache {
+    /// 改写模板，不需要改写的语句缓存为 None
+    entries: Mutex<LruCache<RewriteCacheKey, Option<Arc<RewriteTemplate>>>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl RewriteCache {
+    /// 创建指定容量的缓存
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new(LruCache::new(capacity)),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// 是否启用缓存
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.entries.lock().capacity() > 0
+    }
+
+    /// 查找缓存，同时记录命中与未命中次数
+    pub(crate) fn get(&self, key: &RewriteCacheKey) -> Option<Option<Arc<RewriteTemplate>>> {
+        let found = self.entries.lock().get(key).cloned();
+        let counter = if found.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        found
+    }
+
+    /// 写入缓存，超出容量时淘汰最久未使用的条目
+    pub(crate) fn insert(&self, key: RewriteCacheKey, template: Option<Arc<RewriteTemplate>>) {
+        self.entries.lock().insert(key, template);
+    }
+
+    /// 清空缓存，过滤配置变更后调用
+    pub(crate) fn clear(&self) {
+        self.entries.lock().clear();
+    }
+
+    /// 获取统计信息
+    pub(crate) fn stats(&self) -> RewriteCacheStats {
+        let entries = self.entries.lock();
+        RewriteCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            size: entries.len(),
+            capacity: entries.capacity(),
+        }
+    }
+}
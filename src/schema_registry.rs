@@ -0,0 +1,102 @@
+//! 表结构注册表
+//!
+//! 记录各表实际拥有的字段，默认查询钩子据此只对存在对应字段的表添加过滤条件及填充审计字段。
+//! 字段信息可由 sea-orm 实体的列元数据注册，也可在插件启动时从数据库读取；
+//! 未登记的表视为拥有全部字段，保持原有的过滤行为。
+
+use parking_lot::RwLock;
+use sea_orm::{ConnectionTrait, DatabaseBackend, DbErr, EntityTrait, IdenStatic, Iterable, Statement};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// 表结构注册表，克隆后共享同一份数据
+#[derive(Debug, Clone, Default)]
+pub struct SchemaRegistry {
+    /// 表名到字段名集合的映射，表名和字段名均为小写
+    tables: Arc<RwLock<HashMap<String, HashSet<String>>>>,
+
+    /// 登记内容的版本号，每次变更后递增，用于使改写缓存失效
+    generation: Arc<AtomicU64>,
+}
+
+impl SchemaRegistry {
+    /// 创建空的注册表
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登记表的字段，覆盖该表已登记的字段
+    pub fn register_table<I, S>(&self, table_name: &str, columns: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let columns = columns.into_iter().map(|column| column.as_ref().to_lowercase()).collect();
+        let mut tables = self.tables.write();
+        tables.insert(table_name.to_lowercase(), columns);
+        self.generation.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 根据 sea-orm 实体的列元数据登记表的字段
+    pub fn register_entity<E: EntityTrait>(&self) {
+        let entity = E::default();
+        self.register_table(entity.table_name(), E::Column::iter().map(|column| column.as_str().to_string()));
+    }
+
+    /// 移除表的字段登记
+    pub fn remove_table(&self, table_name: &str) {
+        let mut tables = self.tables.write();
+        tables.remove(&table_name.to_lowercase());
+        self.generation.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 登记内容的版本号
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Relaxed)
+    }
+
+    /// 表是否已登记
+    pub fn contains_table(&self, table_name: &str) -> bool {
+        let tables = self.tables.read();
+        tables.contains_key(&table_name.to_lowercase())
+    }
+
+    /// 检查表是否拥有指定字段，未登记的表视为拥有全部字段
+    pub fn has_column(&self, table_name: &str, column: &str) -> bool {
+        let tables = self.tables.read();
+        match tables.get(&table_name.to_lowercase()) {
+            Some(columns) => columns.contains(&column.to_lowercase()),
+            None => true,
+        }
+    }
+
+    /// 从数据库读取当前库（schema）下所有表的字段并登记，返回登记的表数量
+    pub async fn load_from_database<C: ConnectionTrait>(&self, db: &C) -> Result<usize, DbErr> {
+        let backend = db.get_database_backend();
+        let sql = match backend {
+            DatabaseBackend::MySql => {
+                "SELECT table_name, column_name FROM information_schema.columns WHERE table_schema = DATABASE()"
+            }
+            DatabaseBackend::Postgres => {
+                "SELECT table_name::text, column_name::text FROM information_schema.columns WHERE table_schema = current_schema()"
+            }
+            DatabaseBackend::Sqlite => {
+                "SELECT m.name, p.name FROM sqlite_master m JOIN pragma_table_info(m.name) p WHERE m.type = 'table'"
+            }
+        };
+
+        let mut loaded: HashMap<String, HashSet<String>> = HashMap::new();
+        for row in db.query_all(Statement::from_string(backend, sql)).await? {
+            let table_name: String = row.try_get_by_index(0)?;
+            let column: String = row.try_get_by_index(1)?;
+            loaded.entry(table_name.to_lowercase()).or_default().insert(column.to_lowercase());
+        }
+
+        let count = loaded.len();
+        let mut tables = self.tables.write();
+        tables.extend(loaded);
+        self.generation.fetch_add(1, Ordering::Relaxed);
+        Ok(count)
+    }
+}
@@ -0,0 +1,60 @@
+//! 慢查询日志钩子
+//!
+//! 记录执行耗时超过阈值的语句，日志中包含租户、用户、最终执行的 SQL 及耗时。
+
+use crate::extract_hook::{QueryHook, QueryOutcome};
+use sea_orm::{DatabaseBackend, DbErr, Statement};
+use std::time::Duration;
+
+/// 慢查询日志钩子，只在 `after_query` 中记录日志，不改写语句
+#[derive(Debug, Clone)]
+pub struct SlowQueryHook {
+    /// 慢查询阈值
+    threshold: Duration,
+}
+
+impl SlowQueryHook {
+    /// 创建慢查询日志钩子
+    pub fn new(threshold: Duration) -> Self {
+        Self { threshold }
+    }
+
+    /// 以毫秒为单位的阈值创建慢查询日志钩子
+    pub fn from_millis(threshold_ms: u64) -> Self {
+        Self::new(Duration::from_millis(threshold_ms))
+    }
+
+    /// 慢查询阈值
+    pub fn threshold(&self) -> Duration {
+        self.threshold
+    }
+}
+
+impl QueryHook for SlowQueryHook {
+    fn before_query(&self, _sql: &str, _backend: DatabaseBackend) -> Result<Option<String>, DbErr> {
+        Ok(None)
+    }
+
+    fn before_statement(&self, _stmt: &Statement) -> Result<Option<Statement>, DbErr> {
+        Ok(None)
+    }
+
+    fn after_query(&self, outcome: &QueryOutcome) {
+        if outcome.elapsed < self.threshold {
+            return;
+        }
+        let context = &outcome.context;
+        log::warn!(
+            "Slow query ({:?}, {} ms, tenant: {}, user: {}): {}{}",
+            outcome.backend,
+            outcome.elapsed.as_millis(),
+            context.tenant_id.as_deref().unwrap_or("-"),
+            context.user_name.as_deref().or(context.user_id.as_deref()).unwrap_or("-"),
+            outcome.final_sql(),
+            match outcome.result {
+                Ok(()) => String::new(),
+                Err(e) => format!(", error: {}", e),
+            }
+        );
+    }
+}
@@ -0,0 +1,107 @@
+//! 租户层级
+//!
+//! 上级租户可见下级租户的数据：默认查询钩子在添加租户条件前，通过 `TenantHierarchyProvider`
+//! 将当前可见的租户展开为其全部下级租户。展开结果以 IN 列表注入（缓存的闭包表），
+//! 或以不需要改写连接的子查询注入（递归 CTE）。
+
+use parking_lot::RwLock;
+use sea_orm::{ConnectionTrait, DbErr, Statement};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// 租户层级提供者
+pub trait TenantHierarchyProvider: Send + Sync {
+    /// 获取租户的全部下级租户ID（不含自身），以 IN 列表注入
+    fn descendants(&self, tenant_id: &str) -> Vec<String>;
+
+    /// 查询下级租户ID的子查询，`?` 为上级租户ID的占位符；返回 `Some` 时以子查询代替 IN 列表注入
+    fn descendants_subquery(&self) -> Option<String> {
+        None
+    }
+}
+
+/// 缓存的闭包表，每行记录一对上下级租户（任意层级），克隆后共享同一份数据
+#[derive(Debug, Clone, Default)]
+pub struct ClosureTableHierarchy {
+    /// 上级租户ID到全部下级租户ID的映射
+    descendants: Arc<RwLock<HashMap<String, Vec<String>>>>,
+}
+
+impl ClosureTableHierarchy {
+    /// 创建空的闭包表
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置租户的全部下级租户
+    pub fn set_descendants(&self, tenant_id: &str, descendants: Vec<String>) {
+        let mut cached = self.descendants.write();
+        cached.insert(tenant_id.to_string(), descendants);
+    }
+
+    /// 从数据库读取闭包表并替换缓存，返回读取的上级租户数量
+    pub async fn load_from_database<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        table: &str,
+        ancestor_column: &str,
+        descendant_column: &str,
+    ) -> Result<usize, DbErr> {
+        let backend = db.get_database_backend();
+        let sql = format!("SELECT {}, {} FROM {}", ancestor_column, descendant_column, table);
+
+        let mut loaded: HashMap<String, Vec<String>> = HashMap::new();
+        for row in db.query_all(Statement::from_string(backend, sql)).await? {
+            let ancestor: String = row.try_get_by_index(0)?;
+            let descendant: String = row.try_get_by_index(1)?;
+            // 闭包表通常包含 depth = 0 的自身记录，自身已由租户条件包含
+            if ancestor != descendant {
+                loaded.entry(ancestor).or_default().push(descendant);
+            }
+        }
+
+        let count = loaded.len();
+        *self.descendants.write() = loaded;
+        Ok(count)
+    }
+}
+
+impl TenantHierarchyProvider for ClosureTableHierarchy {
+    fn descendants(&self, tenant_id: &str) -> Vec<String> {
+        let cached = self.descendants.read();
+        cached.get(tenant_id).cloned().unwrap_or_default()
+    }
+}
+
+/// 递归 CTE，由租户表的上级字段逐级查询下级租户，不需要缓存
+#[derive(Debug, Clone)]
+pub struct RecursiveCteHierarchy {
+    /// 递归查询下级租户ID的子查询
+    subquery: String,
+}
+
+impl RecursiveCteHierarchy {
+    /// 根据租户表、租户ID字段及上级租户字段创建
+    pub fn new(table: &str, id_column: &str, parent_column: &str) -> Self {
+        let subquery = format!(
+            "WITH RECURSIVE tenant_tree (id) AS (\
+             SELECT {id} FROM {table} WHERE {parent} = ? \
+             UNION ALL SELECT child.{id} FROM {table} child JOIN tenant_tree ON child.{parent} = tenant_tree.id\
+             ) SELECT id FROM tenant_tree",
+            table = table,
+            id = id_column,
+            parent = parent_column,
+        );
+        Self { subquery }
+    }
+}
+
+impl TenantHierarchyProvider for RecursiveCteHierarchy {
+    fn descendants(&self, _tenant_id: &str) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn descendants_subquery(&self) -> Option<String> {
+        Some(self.subquery.clone())
+    }
+}
@@ -0,0 +1,87 @@
+//! 集成测试共用的上下文设置、改写辅助函数及模拟连接
+
+#![allow(dead_code)]
+
+use auto_field_trait::extract_hook::{DefaultQueryHook, QueryHook};
+use auto_field_trait::{register_clock, register_context_getter, AutoFieldContext, Clock};
+use sea_orm::prelude::DateTime;
+use sea_orm::{ConnectionTrait, DatabaseBackend, DbErr, ExecResult, QueryResult};
+use std::cell::RefCell;
+use std::sync::{Arc, Once};
+
+thread_local! {
+    /// 当前测试线程的上下文，测试并行执行时互不影响
+    static CONTEXT: RefCell<AutoFieldContext> = RefCell::new(AutoFieldContext::default());
+}
+
+static REGISTER: Once = Once::new();
+
+fn thread_context() -> AutoFieldContext {
+    CONTEXT.with(|context| context.borrow().clone())
+}
+
+/// 设置当前测试线程的上下文
+pub fn set_context(context: AutoFieldContext) {
+    REGISTER.call_once(|| register_context_getter(thread_context));
+    CONTEXT.with(|current| *current.borrow_mut() = context);
+}
+
+/// 租户为 t1、用户为 u1/alice 的上下文
+pub fn tenant_context() -> AutoFieldContext {
+    AutoFieldContext::default()
+        .with_tenant(Some("t1".to_string()), None)
+        .with_user(Some("u1".to_string()), Some("alice".to_string()), None)
+}
+
+/// 固定时间的时钟
+struct FixedClock;
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime {
+        DateTime::parse_from_str("2024-01-02 03:04:05", "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+}
+
+/// 使用固定为 `2024-01-02 03:04:05` 的时钟，内联时间字面量为 `'2024-01-02 03:04:05.000000'`
+pub fn use_fixed_clock() {
+    register_clock(Arc::new(FixedClock));
+}
+
+/// 在单线程运行时中执行 Future，用于测试 task_local 作用域
+pub fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap().block_on(future)
+}
+
+/// 以内联模式改写 SQL，不需要改写时返回原 SQL
+pub fn rewrite(hook: &DefaultQueryHook, backend: DatabaseBackend, sql: &str) -> String {
+    hook.before_query(sql, backend)
+        .unwrap_or_else(|e| panic!("rewrite failed for {:?}: {}, error: {}", backend, sql, e))
+        .unwrap_or_else(|| sql.to_string())
+}
+
+/// 不返回任何行、不支持 execute 的 MySQL 连接
+#[derive(Clone)]
+pub struct EmptyConnection;
+
+#[async_trait::async_trait]
+impl ConnectionTrait for EmptyConnection {
+    fn get_database_backend(&self) -> DatabaseBackend {
+        DatabaseBackend::MySql
+    }
+
+    async fn execute(&self, _stmt: sea_orm::Statement) -> Result<ExecResult, DbErr> {
+        Err(DbErr::Custom("execute is not supported".to_string()))
+    }
+
+    async fn execute_unprepared(&self, _sql: &str) -> Result<ExecResult, DbErr> {
+        Err(DbErr::Custom("execute is not supported".to_string()))
+    }
+
+    async fn query_one(&self, _stmt: sea_orm::Statement) -> Result<Option<QueryResult>, DbErr> {
+        Ok(None)
+    }
+
+    async fn query_all(&self, _stmt: sea_orm::Statement) -> Result<Vec<QueryResult>, DbErr> {
+        Ok(Vec::new())
+    }
+}
@@ -0,0 +1,59 @@
+//! 关闭过滤条件的连接视图的审计日志测试
+
+mod common;
+
+use auto_field_trait::extract_hook::{DefaultQueryHook, HookedConnection};
+use common::{block_on, set_context, tenant_context, EmptyConnection};
+use parking_lot::Mutex;
+use sea_orm::{ConnectionTrait, DatabaseBackend, Statement};
+use std::sync::Arc;
+
+/// 收集 `audit` target 日志的记录器
+struct AuditLogger;
+
+static AUDIT_RECORDS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+impl log::Log for AuditLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.target() == "audit"
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            AUDIT_RECORDS.lock().push(record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+fn audit_records(pattern: &str) -> Vec<String> {
+    AUDIT_RECORDS.lock().iter().filter(|record| record.contains(pattern)).cloned().collect()
+}
+
+#[test]
+fn statements_on_unfiltered_views_are_audited() {
+    log::set_logger(&AuditLogger).unwrap();
+    log::set_max_level(log::LevelFilter::Warn);
+    set_context(tenant_context());
+
+    let conn = HookedConnection::new(EmptyConnection, Arc::new(DefaultQueryHook::new(true, true)));
+    let unfiltered = conn.unfiltered();
+    assert_eq!(audit_records("filters disabled: All").len(), 1);
+
+    let select = |sql: &str| Statement::from_string(DatabaseBackend::MySql, sql);
+    block_on(async {
+        conn.query_all(select("SELECT * FROM audited_orders")).await.unwrap();
+        unfiltered.query_all(select("SELECT * FROM audited_orders")).await.unwrap();
+        unfiltered.query_one(select("SELECT * FROM audited_orders WHERE id = 1")).await.unwrap();
+        unfiltered.execute_unprepared("DELETE FROM audited_orders").await.unwrap_err();
+    });
+
+    let records = audit_records("statement with filters disabled: All");
+    assert_eq!(records.len(), 3);
+    assert_eq!(
+        records[0],
+        "statement with filters disabled: All, sql: SELECT * FROM audited_orders, tenant: t1, user: alice"
+    );
+    assert!(records[2].contains("sql: DELETE FROM audited_orders"));
+}
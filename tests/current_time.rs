@@ -0,0 +1,87 @@
+//! 当前时间测试：同一语句中的多行及多个时间字段只读取一次时钟
+
+mod common;
+
+use auto_field_trait::config::{FilterColumnConfig, SoftDeleteStrategy};
+use auto_field_trait::extract_hook::{DefaultQueryHook, QueryHook};
+use auto_field_trait::{register_clock, Clock};
+use common::{rewrite, set_context, tenant_context};
+use sea_orm::prelude::DateTime;
+use sea_orm::{DatabaseBackend, Statement, Value};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 每次读取都前进一秒的时钟
+struct AdvancingClock(AtomicU64);
+
+impl Clock for AdvancingClock {
+    fn now(&self) -> DateTime {
+        let seconds = self.0.fetch_add(1, Ordering::SeqCst);
+        DateTime::parse_from_str("2024-01-02 03:04:05", "%Y-%m-%d %H:%M:%S").unwrap() + Duration::from_secs(seconds)
+    }
+}
+
+/// 开启 INSERT 填充及时间戳策略的软删除改写，使用前进的时钟
+fn advancing_clock_hook() -> DefaultQueryHook {
+    set_context(tenant_context());
+    register_clock(Arc::new(AdvancingClock(AtomicU64::new(0))));
+    let hook = DefaultQueryHook::new(true, true)
+        .with_insert_fill(true)
+        .with_soft_delete_rewrite(true);
+    hook.set_filter_columns(FilterColumnConfig {
+        soft_delete_strategy: Some(SoftDeleteStrategy::Timestamp),
+        soft_delete_column: Some("deleted_at".to_string()),
+        ..Default::default()
+    });
+    hook
+}
+
+/// 取出语句中的全部时间参数
+fn time_values(stmt: &Statement) -> Vec<Value> {
+    let values = stmt.values.as_ref().expect("statement has no values");
+    values.0.iter().filter(|value| matches!(value, Value::ChronoDateTime(_))).cloned().collect()
+}
+
+#[test]
+fn multi_row_insert_rows_share_the_current_time() {
+    let hook = advancing_clock_hook();
+    for (backend, sql) in [
+        (DatabaseBackend::MySql, "INSERT INTO orders (id) VALUES (?), (?), (?)"),
+        (DatabaseBackend::Postgres, "INSERT INTO orders (id) VALUES ($1), ($2), ($3)"),
+    ] {
+        let stmt = Statement::from_sql_and_values(backend, sql, [1.into(), 2.into(), 3.into()]);
+        // 第二次执行命中缓存，由模板生成参数
+        for _ in 0..2 {
+            let rewritten = hook.before_statement(&stmt).unwrap().expect("statement was not rewritten");
+            let times = time_values(&rewritten);
+            assert!(!times.is_empty());
+            assert!(times.iter().all(|time| *time == times[0]), "{:?}: {:?}", backend, times);
+        }
+    }
+
+    // 内联模式下各行的时间字面量相同
+    let sql = rewrite(&hook, DatabaseBackend::MySql, "INSERT INTO orders (id) VALUES (1), (2)");
+    let first = sql.find("'2024-01-02").unwrap();
+    let second = sql.rfind("'2024-01-02").unwrap();
+    assert!(first < second);
+    assert_eq!(sql[first..first + 28], sql[second..second + 28]);
+}
+
+#[test]
+fn soft_delete_time_matches_update_time() {
+    let hook = advancing_clock_hook();
+    let stmt = Statement::from_sql_and_values(DatabaseBackend::Postgres, "DELETE FROM orders WHERE id = $1", [1.into()]);
+    for _ in 0..2 {
+        let rewritten = hook.before_statement(&stmt).unwrap().expect("statement was not rewritten");
+        assert!(rewritten.sql.starts_with("UPDATE orders SET deleted_at = $2, update_time = $3"));
+        let times = time_values(&rewritten);
+        assert_eq!(times.len(), 2);
+        assert_eq!(times[0], times[1]);
+    }
+
+    let sql = rewrite(&hook, DatabaseBackend::MySql, "DELETE FROM orders WHERE id = 1");
+    let deleted_at = &sql[sql.find("deleted_at = ").unwrap() + 13..][..28];
+    let update_time = &sql[sql.find("update_time = ").unwrap() + 14..][..28];
+    assert_eq!(deleted_at, update_time);
+}
@@ -0,0 +1,111 @@
+//! 数据权限测试：按范围添加部门或创建人条件，没有可见部门时不可见任何行，可按任务单独关闭
+
+mod common;
+
+use auto_field_trait::config::FilterColumnConfig;
+use auto_field_trait::extract_hook::{DefaultQueryHook, QueryHook};
+use auto_field_trait::{without_filters, DataScope, FilterKind};
+use common::{block_on, rewrite, set_context, tenant_context};
+use sea_orm::{DatabaseBackend, Statement, Value};
+
+/// 开启数据权限，部门字段为 dept_id，创建人字段为 create_id
+fn data_scope_hook() -> DefaultQueryHook {
+    let hook = DefaultQueryHook::new(true, true).with_data_scope(true);
+    hook.set_filter_columns(FilterColumnConfig {
+        dept_column: Some("dept_id".to_string()),
+        owner_column: Some("create_id".to_string()),
+        ..Default::default()
+    });
+    hook
+}
+
+fn scoped_rewrite(hook: &DefaultQueryHook, dept_id: Option<&str>, scope: DataScope) -> String {
+    set_context(tenant_context().with_data_scope(dept_id.map(str::to_string), scope));
+    rewrite(hook, DatabaseBackend::MySql, "SELECT * FROM orders")
+}
+
+#[test]
+fn scope_adds_dept_or_owner_condition() {
+    let hook = data_scope_hook();
+    assert_eq!(
+        scoped_rewrite(&hook, Some("d1"), DataScope::Dept),
+        "SELECT * FROM orders WHERE (delete_flag = 0 AND tenant_id = 't1' AND dept_id = 'd1')"
+    );
+    assert_eq!(
+        scoped_rewrite(&hook, Some("d1"), DataScope::DeptAndSub(vec!["d2".to_string(), "d1".to_string()])),
+        "SELECT * FROM orders WHERE (delete_flag = 0 AND tenant_id = 't1' AND dept_id IN ('d1', 'd2'))"
+    );
+    assert_eq!(
+        scoped_rewrite(&hook, Some("d1"), DataScope::Own),
+        "SELECT * FROM orders WHERE (delete_flag = 0 AND tenant_id = 't1' AND create_id = 'u1')"
+    );
+    assert_eq!(
+        scoped_rewrite(&hook, Some("d1"), DataScope::All),
+        "SELECT * FROM orders WHERE (delete_flag = 0 AND tenant_id = 't1')"
+    );
+}
+
+#[test]
+fn scope_without_visible_dept_fails_closed() {
+    let hook = data_scope_hook();
+    assert_eq!(
+        scoped_rewrite(&hook, None, DataScope::Dept),
+        "SELECT * FROM orders WHERE (delete_flag = 0 AND tenant_id = 't1' AND false)"
+    );
+    assert_eq!(
+        scoped_rewrite(&hook, Some("d1"), DataScope::Custom(Vec::new())),
+        "SELECT * FROM orders WHERE (delete_flag = 0 AND tenant_id = 't1' AND false)"
+    );
+
+    let stmt = Statement::from_sql_and_values(DatabaseBackend::MySql, "SELECT * FROM orders WHERE id = ?", [Value::from(1)]);
+    set_context(tenant_context().with_data_scope(None, DataScope::Dept));
+    let rewritten = hook.before_statement(&stmt).unwrap().expect("statement was not rewritten");
+    assert_eq!(rewritten.sql, "SELECT * FROM orders WHERE id = ? AND (delete_flag = 0 AND tenant_id = ? AND false)");
+    assert_eq!(rewritten.values.unwrap().0, [Value::from(1), Value::from("t1")]);
+}
+
+#[test]
+fn tables_without_scope_columns_are_not_restricted() {
+    let hook = data_scope_hook();
+    hook.schema_registry().register_table("region", ["id", "tenant_id", "delete_flag"]);
+    set_context(tenant_context().with_data_scope(None, DataScope::Dept));
+    assert_eq!(
+        rewrite(&hook, DatabaseBackend::MySql, "SELECT * FROM region"),
+        "SELECT * FROM region WHERE (delete_flag = 0 AND tenant_id = 't1')"
+    );
+}
+
+#[test]
+fn data_scope_can_be_disabled_alone() {
+    let hook = data_scope_hook();
+    set_context(tenant_context().with_data_scope(Some("d1".to_string()), DataScope::Dept));
+    let stmt = Statement::from_sql_and_values(DatabaseBackend::MySql, "SELECT * FROM orders WHERE id = ?", [Value::from(1)]);
+
+    // 先在作用域外缓存带数据权限条件的模板，作用域内不能命中
+    let scoped = hook.before_statement(&stmt).unwrap().expect("statement was not rewritten");
+    assert_eq!(scoped.sql, "SELECT * FROM orders WHERE id = ? AND (delete_flag = 0 AND tenant_id = ? AND dept_id = ?)");
+
+    block_on(without_filters(FilterKind::DataScope, async {
+        assert_eq!(
+            rewrite(&hook, DatabaseBackend::MySql, "SELECT * FROM orders"),
+            "SELECT * FROM orders WHERE (delete_flag = 0 AND tenant_id = 't1')"
+        );
+        let unscoped = hook.before_statement(&stmt).unwrap().expect("statement was not rewritten");
+        assert_eq!(unscoped.sql, "SELECT * FROM orders WHERE id = ? AND (delete_flag = 0 AND tenant_id = ?)");
+    }));
+
+    // 关闭全部过滤同样不添加数据权限条件
+    block_on(without_filters(FilterKind::All, async {
+        assert_eq!(rewrite(&hook, DatabaseBackend::MySql, "SELECT * FROM orders"), "SELECT * FROM orders");
+    }));
+}
+
+#[test]
+fn filter_kinds_combine() {
+    let kind = FilterKind::Tenant.union(FilterKind::DataScope);
+    assert!(kind.contains(FilterKind::DataScope));
+    assert!(!kind.contains(FilterKind::SoftDelete));
+    assert_eq!(format!("{:?}", kind), "Tenant | DataScope");
+    assert_eq!(kind.union(FilterKind::SoftDelete), FilterKind::All);
+    assert!(FilterKind::All.contains(FilterKind::DataScope));
+}
@@ -0,0 +1,76 @@
+//! 过滤字段配置测试：租户取值来源、自定义未删除值及按表配置与全局配置的合并
+
+mod common;
+
+use auto_field_trait::config::{FilterColumnConfig, FilterValue, TenantValueSource};
+use auto_field_trait::extract_hook::{DefaultQueryHook, QueryHook};
+use common::{rewrite, set_context, tenant_context};
+use sea_orm::{DatabaseBackend, Statement, Value};
+
+#[test]
+fn tenant_value_can_come_from_tenant_name() {
+    set_context(tenant_context().with_tenant(Some("t1".to_string()), Some("acme".to_string())));
+    let hook = DefaultQueryHook::new(true, true);
+    hook.set_filter_columns(FilterColumnConfig {
+        tenant_column: Some("tenant_code".to_string()),
+        tenant_value_source: Some(TenantValueSource::TenantName),
+        ..Default::default()
+    });
+
+    assert_eq!(
+        rewrite(&hook, DatabaseBackend::MySql, "SELECT * FROM orders"),
+        "SELECT * FROM orders WHERE (delete_flag = 0 AND tenant_code = 'acme')"
+    );
+
+    let stmt = Statement::from_sql_and_values(DatabaseBackend::Postgres, "SELECT * FROM orders WHERE id = $1", [Value::from(1)]);
+    let rewritten = hook.before_statement(&stmt).unwrap().expect("statement was not rewritten");
+    assert_eq!(rewritten.sql, "SELECT * FROM orders WHERE id = $1 AND (delete_flag = 0 AND tenant_code = $2)");
+    assert_eq!(rewritten.values.unwrap().0, [Value::from(1), Value::from("acme")]);
+}
+
+#[test]
+fn custom_not_deleted_value_is_used() {
+    set_context(tenant_context());
+    let hook = DefaultQueryHook::new(true, true);
+    hook.set_filter_columns(FilterColumnConfig {
+        soft_delete_column: Some("del_flag".to_string()),
+        not_deleted_value: Some(FilterValue::Text("N".to_string())),
+        ..Default::default()
+    });
+
+    assert_eq!(
+        rewrite(&hook, DatabaseBackend::MySql, "SELECT * FROM orders"),
+        "SELECT * FROM orders WHERE (del_flag = 'N' AND tenant_id = 't1')"
+    );
+}
+
+#[test]
+fn table_override_is_merged_with_global_config() {
+    set_context(tenant_context());
+    let hook = DefaultQueryHook::new(true, true);
+    hook.set_filter_columns(FilterColumnConfig {
+        not_deleted_value: Some(FilterValue::Text("N".to_string())),
+        ..Default::default()
+    });
+    hook.set_table_filter_columns(
+        "legacy",
+        FilterColumnConfig {
+            tenant_column: Some("org_id".to_string()),
+            ..Default::default()
+        },
+    );
+
+    // 按表配置只覆盖租户字段，未删除值沿用全局配置
+    assert_eq!(
+        rewrite(&hook, DatabaseBackend::MySql, "SELECT * FROM legacy l JOIN orders o ON o.id = l.order_id"),
+        "SELECT * FROM legacy l JOIN orders o ON o.id = l.order_id \
+         WHERE (l.delete_flag = 'N' AND l.org_id = 't1' AND o.delete_flag = 'N' AND o.tenant_id = 't1')"
+    );
+
+    // 移除按表配置后回到全局配置
+    hook.remove_table_filter_columns("legacy");
+    assert_eq!(
+        rewrite(&hook, DatabaseBackend::MySql, "SELECT * FROM legacy"),
+        "SELECT * FROM legacy WHERE (delete_flag = 'N' AND tenant_id = 't1')"
+    );
+}
@@ -0,0 +1,80 @@
+//! 钩子链测试：同步与异步钩子按优先级执行，移除钩子，以同步钩子执行
+
+mod common;
+
+use auto_field_trait::extract_hook::{AsyncQueryHook, QueryHook, QueryOutcome};
+use auto_field_trait::{HookChain, HookErrorKind};
+use common::block_on;
+use sea_orm::{DatabaseBackend, DbErr};
+use std::sync::Arc;
+
+/// 在 SQL 末尾追加注释的同步钩子
+struct AppendHook(&'static str);
+
+impl QueryHook for AppendHook {
+    fn before_query(&self, sql: &str, _backend: DatabaseBackend) -> Result<Option<String>, DbErr> {
+        Ok(Some(format!("{} /* {} */", sql, self.0)))
+    }
+
+    fn after_query(&self, _outcome: &QueryOutcome) {}
+}
+
+/// 让出执行权后再追加注释的异步钩子
+struct AsyncAppendHook(&'static str);
+
+#[async_trait::async_trait]
+impl AsyncQueryHook for AsyncAppendHook {
+    async fn before_query(&self, sql: &str, _backend: DatabaseBackend) -> Result<Option<String>, DbErr> {
+        tokio::task::yield_now().await;
+        Ok(Some(format!("{} /* {} */", sql, self.0)))
+    }
+
+    async fn after_query(&self, _outcome: &QueryOutcome<'_>) {}
+}
+
+#[test]
+fn sync_and_async_hooks_run_in_priority_order() {
+    let chain = HookChain::new()
+        .with_hook(Arc::new(AppendHook("second")), 10)
+        .with_async_hook(Arc::new(AsyncAppendHook("first")), -10)
+        .with_async_hook(Arc::new(AsyncAppendHook("third")), 10);
+
+    let sql = block_on(AsyncQueryHook::before_query(&chain, "SELECT 1", DatabaseBackend::MySql)).unwrap();
+    assert_eq!(sql.as_deref(), Some("SELECT 1 /* first */ /* second */ /* third */"));
+}
+
+#[test]
+fn hooks_are_removed_by_the_added_instance() {
+    let sync_hook: Arc<dyn QueryHook> = Arc::new(AppendHook("sync"));
+    let async_hook: Arc<dyn AsyncQueryHook> = Arc::new(AsyncAppendHook("async"));
+    let chain = HookChain::new()
+        .with_hook(sync_hook.clone(), 0)
+        .with_async_hook(async_hook.clone(), 0);
+
+    assert!(chain.remove_hook(&sync_hook));
+    assert!(!chain.remove_hook(&sync_hook));
+    assert_eq!(chain.len(), 1);
+    let sql = block_on(AsyncQueryHook::before_query(&chain, "SELECT 1", DatabaseBackend::MySql)).unwrap();
+    assert_eq!(sql.as_deref(), Some("SELECT 1 /* async */"));
+
+    assert!(chain.remove_async_hook(&async_hook));
+    assert!(chain.is_empty());
+}
+
+#[test]
+fn chain_runs_as_sync_hook() {
+    let chain = HookChain::new()
+        .with_hook(Arc::new(AppendHook("second")), 10)
+        .with_hook(Arc::new(AppendHook("first")), -10);
+    assert!(!chain.has_async_hooks());
+
+    let hook: Arc<dyn QueryHook> = Arc::new(chain.clone());
+    let sql = hook.before_query("SELECT 1", DatabaseBackend::MySql).unwrap();
+    assert_eq!(sql.as_deref(), Some("SELECT 1 /* first */ /* second */"));
+
+    // 同步执行时无法等待异步钩子，拒绝执行而不是跳过
+    chain.add_async_hook(Arc::new(AsyncAppendHook("async")), 0);
+    assert!(chain.has_async_hooks());
+    let err = hook.before_query("SELECT 1", DatabaseBackend::MySql).unwrap_err();
+    assert_eq!(HookErrorKind::of(&err), Some(HookErrorKind::AsyncHook));
+}
@@ -0,0 +1,139 @@
+//! 连接查询的过滤条件测试：外连接中可为空一侧的条件放入 ON（USING、NATURAL 连接改为派生表），其余放入 WHERE，跳过表按连接中的每张表判断
+
+mod common;
+
+use auto_field_trait::extract_hook::{DefaultQueryHook, QueryHook};
+use auto_field_trait::HookErrorKind;
+use common::{rewrite, set_context, tenant_context};
+use sea_orm::{DatabaseBackend, Statement, Value};
+
+fn hook() -> DefaultQueryHook {
+    set_context(tenant_context());
+    DefaultQueryHook::new(true, true)
+}
+
+#[test]
+fn single_table_is_not_qualified() {
+    let sql = rewrite(&hook(), DatabaseBackend::MySql, "SELECT * FROM orders");
+    assert_eq!(sql, "SELECT * FROM orders WHERE (delete_flag = 0 AND tenant_id = 't1')");
+}
+
+#[test]
+fn inner_join_conditions_go_to_where() {
+    let sql = rewrite(
+        &hook(),
+        DatabaseBackend::MySql,
+        "SELECT * FROM orders o JOIN users u ON u.id = o.user_id WHERE o.amount > 1 OR o.amount < 0",
+    );
+    assert_eq!(
+        sql,
+        "SELECT * FROM orders o JOIN users u ON u.id = o.user_id WHERE (o.amount > 1 OR o.amount < 0) \
+         AND (o.delete_flag = 0 AND o.tenant_id = 't1' AND u.delete_flag = 0 AND u.tenant_id = 't1')"
+    );
+}
+
+#[test]
+fn left_join_conditions_go_to_on() {
+    let sql = rewrite(&hook(), DatabaseBackend::MySql, "SELECT * FROM orders o LEFT JOIN users u ON u.id = o.user_id");
+    assert_eq!(
+        sql,
+        "SELECT * FROM orders o LEFT JOIN users u ON u.id = o.user_id AND (u.delete_flag = 0 AND u.tenant_id = 't1') \
+         WHERE (o.delete_flag = 0 AND o.tenant_id = 't1')"
+    );
+}
+
+#[test]
+fn right_join_left_side_conditions_go_to_on() {
+    let sql = rewrite(&hook(), DatabaseBackend::MySql, "SELECT * FROM orders o RIGHT JOIN users u ON u.id = o.user_id");
+    assert_eq!(
+        sql,
+        "SELECT * FROM orders o RIGHT JOIN users u ON u.id = o.user_id AND (o.delete_flag = 0 AND o.tenant_id = 't1') \
+         WHERE (u.delete_flag = 0 AND u.tenant_id = 't1')"
+    );
+}
+
+#[test]
+fn full_join_conditions_go_to_where() {
+    let sql = rewrite(&hook(), DatabaseBackend::Postgres, "SELECT * FROM orders o FULL OUTER JOIN users u ON u.id = o.user_id");
+    assert_eq!(
+        sql,
+        "SELECT * FROM orders o FULL JOIN users u ON u.id = o.user_id \
+         WHERE (o.delete_flag = 0 AND o.tenant_id = 't1' AND u.delete_flag = 0 AND u.tenant_id = 't1')"
+    );
+}
+
+#[test]
+fn outer_join_using_filters_nullable_side_in_derived_table() {
+    let sql = rewrite(&hook(), DatabaseBackend::MySql, "SELECT * FROM orders o LEFT JOIN users u USING (user_id)");
+    assert_eq!(
+        sql,
+        "SELECT * FROM orders o LEFT JOIN (SELECT * FROM users u WHERE (u.delete_flag = 0 AND u.tenant_id = 't1')) u USING(user_id) \
+         WHERE (o.delete_flag = 0 AND o.tenant_id = 't1')"
+    );
+
+    let sql = rewrite(&hook(), DatabaseBackend::Postgres, "SELECT * FROM orders NATURAL LEFT JOIN users");
+    assert_eq!(
+        sql,
+        "SELECT * FROM orders NATURAL LEFT JOIN (SELECT * FROM users WHERE (users.delete_flag = 0 AND users.tenant_id = 't1')) AS users \
+         WHERE (orders.delete_flag = 0 AND orders.tenant_id = 't1')"
+    );
+
+    let sql = rewrite(&hook(), DatabaseBackend::Postgres, "SELECT * FROM orders o RIGHT JOIN users u USING (user_id)");
+    assert_eq!(
+        sql,
+        "SELECT * FROM (SELECT * FROM orders o WHERE (o.delete_flag = 0 AND o.tenant_id = 't1')) o RIGHT JOIN users u USING(user_id) \
+         WHERE (u.delete_flag = 0 AND u.tenant_id = 't1')"
+    );
+}
+
+#[test]
+fn outer_join_using_keeps_bound_parameter_order() {
+    let stmt = Statement::from_sql_and_values(
+        DatabaseBackend::MySql,
+        "SELECT * FROM orders o LEFT JOIN users u USING (user_id) WHERE o.id = ?",
+        [Value::from(1)],
+    );
+    let rewritten = hook().before_statement(&stmt).unwrap().expect("statement was not rewritten");
+    assert_eq!(
+        rewritten.sql,
+        "SELECT * FROM orders o LEFT JOIN (SELECT * FROM users u WHERE (u.delete_flag = 0 AND u.tenant_id = ?)) u USING(user_id) \
+         WHERE o.id = ? AND (o.delete_flag = 0 AND o.tenant_id = ?)"
+    );
+    assert_eq!(rewritten.values.unwrap().0, [Value::from("t1"), Value::from(1), Value::from("t1")]);
+}
+
+#[test]
+fn unfilterable_outer_join_using_is_rejected() {
+    for sql in [
+        "SELECT * FROM orders o JOIN items i ON i.order_id = o.id RIGHT JOIN users u USING (user_id)",
+        "SELECT * FROM orders o LEFT JOIN (users u JOIN depts d ON d.id = u.dept_id) USING (user_id)",
+    ] {
+        let err = hook().before_query(sql, DatabaseBackend::Postgres).unwrap_err();
+        assert_eq!(HookErrorKind::of(&err), Some(HookErrorKind::UnfilterableJoin), "{}", sql);
+    }
+}
+
+#[test]
+fn comma_join_without_alias_is_qualified_by_table_name() {
+    let sql = rewrite(&hook(), DatabaseBackend::MySql, "SELECT * FROM orders, users WHERE orders.user_id = users.id");
+    assert_eq!(
+        sql,
+        "SELECT * FROM orders, users WHERE orders.user_id = users.id \
+         AND (orders.delete_flag = 0 AND orders.tenant_id = 't1' AND users.delete_flag = 0 AND users.tenant_id = 't1')"
+    );
+}
+
+#[test]
+fn skip_list_applies_per_joined_table() {
+    let hook = hook();
+    hook.add_skip_table("sys_dict");
+    let sql = rewrite(&hook, DatabaseBackend::MySql, "SELECT * FROM orders o LEFT JOIN sys_dict d ON d.code = o.status");
+    assert_eq!(
+        sql,
+        "SELECT * FROM orders o LEFT JOIN sys_dict d ON d.code = o.status WHERE (o.delete_flag = 0 AND o.tenant_id = 't1')"
+    );
+
+    hook.add_skip_table("orders");
+    let sql = rewrite(&hook, DatabaseBackend::MySql, "SELECT * FROM orders o LEFT JOIN sys_dict d ON d.code = o.status");
+    assert_eq!(sql, "SELECT * FROM orders o LEFT JOIN sys_dict d ON d.code = o.status");
+}
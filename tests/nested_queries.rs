@@ -0,0 +1,203 @@
+//! 嵌套查询的过滤条件测试：每个子查询都在自身作用域中添加条件
+
+mod common;
+
+use auto_field_trait::extract_hook::DefaultQueryHook;
+use common::{rewrite, set_context, tenant_context};
+use sea_orm::DatabaseBackend;
+
+/// 只开启软删除过滤，便于核对每个作用域的条件
+fn soft_delete_hook() -> DefaultQueryHook {
+    set_context(tenant_context());
+    DefaultQueryHook::new(true, false)
+}
+
+#[test]
+fn where_and_projection_subqueries_are_filtered() {
+    let sql = rewrite(
+        &soft_delete_hook(),
+        DatabaseBackend::MySql,
+        "SELECT a.id, (SELECT count(*) FROM c WHERE c.a_id = a.id) FROM a WHERE EXISTS (SELECT 1 FROM b WHERE b.a_id = a.id)",
+    );
+    assert_eq!(
+        sql,
+        "SELECT a.id, (SELECT count(*) FROM c WHERE c.a_id = a.id AND delete_flag = 0) FROM a \
+         WHERE EXISTS (SELECT 1 FROM b WHERE b.a_id = a.id AND delete_flag = 0) AND delete_flag = 0"
+    );
+}
+
+#[test]
+fn order_by_subquery_is_filtered() {
+    let sql = rewrite(
+        &soft_delete_hook(),
+        DatabaseBackend::MySql,
+        "SELECT * FROM a ORDER BY (SELECT max(x) FROM b WHERE b.id = a.id)",
+    );
+    assert_eq!(
+        sql,
+        "SELECT * FROM a WHERE delete_flag = 0 ORDER BY (SELECT max(x) FROM b WHERE b.id = a.id AND delete_flag = 0)"
+    );
+}
+
+#[test]
+fn limit_and_offset_subqueries_are_filtered() {
+    let sql = rewrite(
+        &soft_delete_hook(),
+        DatabaseBackend::Postgres,
+        "SELECT * FROM a LIMIT (SELECT count(*) FROM b) OFFSET (SELECT min(n) FROM c)",
+    );
+    assert_eq!(
+        sql,
+        "SELECT * FROM a WHERE delete_flag = 0 LIMIT (SELECT count(*) FROM b WHERE delete_flag = 0) \
+         OFFSET (SELECT min(n) FROM c WHERE delete_flag = 0)"
+    );
+}
+
+#[test]
+fn table_function_arguments_are_filtered() {
+    let hook = soft_delete_hook();
+    // 表函数本身不是表，不添加条件
+    let sql = rewrite(
+        &hook,
+        DatabaseBackend::Postgres,
+        "SELECT * FROM generate_series(1, (SELECT max(id) FROM b)) AS g",
+    );
+    assert_eq!(sql, "SELECT * FROM generate_series(1, (SELECT max(id) FROM b WHERE delete_flag = 0)) AS g");
+
+    let sql = rewrite(&hook, DatabaseBackend::Postgres, "SELECT * FROM UNNEST(ARRAY(SELECT id FROM b)) AS u");
+    assert_eq!(sql, "SELECT * FROM UNNEST(ARRAY(SELECT id FROM b WHERE delete_flag = 0)) AS u");
+}
+
+#[test]
+fn derived_table_is_filtered_in_its_own_scope() {
+    let sql = rewrite(
+        &soft_delete_hook(),
+        DatabaseBackend::MySql,
+        "SELECT t.id FROM (SELECT id FROM b) AS t JOIN a ON a.id = t.id",
+    );
+    assert_eq!(
+        sql,
+        "SELECT t.id FROM (SELECT id FROM b WHERE delete_flag = 0) AS t JOIN a ON a.id = t.id WHERE a.delete_flag = 0"
+    );
+}
+
+/// 开启软删除及租户过滤
+fn tenant_hook() -> DefaultQueryHook {
+    set_context(tenant_context());
+    DefaultQueryHook::new(true, true)
+}
+
+#[test]
+fn cte_definition_sees_the_real_table_with_the_same_name() {
+    let sql = rewrite(
+        &tenant_hook(),
+        DatabaseBackend::Postgres,
+        "WITH orders AS (SELECT * FROM orders WHERE amount > 10) SELECT * FROM orders",
+    );
+    assert_eq!(
+        sql,
+        "WITH orders AS (SELECT * FROM orders WHERE amount > 10 AND (delete_flag = 0 AND tenant_id = 't1')) SELECT * FROM orders"
+    );
+}
+
+#[test]
+fn cte_name_is_only_visible_inside_its_query() {
+    let sql = rewrite(
+        &tenant_hook(),
+        DatabaseBackend::Postgres,
+        "SELECT * FROM orders WHERE id IN (WITH orders AS (SELECT 1 AS id) SELECT id FROM orders)",
+    );
+    assert_eq!(
+        sql,
+        "SELECT * FROM orders WHERE id IN (WITH orders AS (SELECT 1 AS id) SELECT id FROM orders) \
+         AND (delete_flag = 0 AND tenant_id = 't1')"
+    );
+}
+
+#[test]
+fn later_cte_references_earlier_cte_without_filter() {
+    let sql = rewrite(
+        &tenant_hook(),
+        DatabaseBackend::Postgres,
+        "WITH big AS (SELECT id FROM orders WHERE amount > 10), pick AS (SELECT id FROM big) \
+         SELECT * FROM pick JOIN users ON users.id = pick.id",
+    );
+    assert_eq!(
+        sql,
+        "WITH big AS (SELECT id FROM orders WHERE amount > 10 AND (delete_flag = 0 AND tenant_id = 't1')), \
+         pick AS (SELECT id FROM big) SELECT * FROM pick JOIN users ON users.id = pick.id \
+         WHERE (users.delete_flag = 0 AND users.tenant_id = 't1')"
+    );
+}
+
+#[test]
+fn recursive_cte_references_itself_without_filter() {
+    let sql = rewrite(
+        &tenant_hook(),
+        DatabaseBackend::Postgres,
+        "WITH RECURSIVE tree AS (SELECT id FROM depts WHERE parent_id IS NULL \
+         UNION ALL SELECT d.id FROM depts d JOIN tree ON d.parent_id = tree.id) SELECT * FROM tree",
+    );
+    assert_eq!(
+        sql,
+        "WITH RECURSIVE tree AS (SELECT id FROM depts WHERE parent_id IS NULL AND (delete_flag = 0 AND tenant_id = 't1') \
+         UNION ALL SELECT d.id FROM depts d JOIN tree ON d.parent_id = tree.id \
+         WHERE (d.delete_flag = 0 AND d.tenant_id = 't1')) SELECT * FROM tree"
+    );
+}
+
+#[test]
+fn cte_before_update_is_scoped_to_the_update() {
+    set_context(tenant_context());
+    let hook = DefaultQueryHook::new(true, true).with_update_filter(true);
+    let sql = rewrite(
+        &hook,
+        DatabaseBackend::Postgres,
+        "WITH old AS (SELECT id FROM orders WHERE amount < 1) UPDATE orders SET amount = 0 WHERE id IN (SELECT id FROM old)",
+    );
+    assert_eq!(
+        sql,
+        "WITH old AS (SELECT id FROM orders WHERE amount < 1 AND (delete_flag = 0 AND tenant_id = 't1')) \
+         UPDATE orders SET amount = 0 WHERE id IN (SELECT id FROM old) AND (delete_flag = 0 AND tenant_id = 't1')"
+    );
+}
+
+#[test]
+fn every_set_operation_branch_is_filtered() {
+    let hook = tenant_hook();
+    let sql = rewrite(&hook, DatabaseBackend::MySql, "SELECT id FROM a UNION SELECT id FROM b");
+    assert_eq!(
+        sql,
+        "SELECT id FROM a WHERE (delete_flag = 0 AND tenant_id = 't1') \
+         UNION SELECT id FROM b WHERE (delete_flag = 0 AND tenant_id = 't1')"
+    );
+
+    let sql = rewrite(
+        &hook,
+        DatabaseBackend::Postgres,
+        "SELECT id FROM a INTERSECT SELECT id FROM b EXCEPT SELECT id FROM c",
+    );
+    assert_eq!(
+        sql,
+        "SELECT id FROM a WHERE (delete_flag = 0 AND tenant_id = 't1') \
+         INTERSECT SELECT id FROM b WHERE (delete_flag = 0 AND tenant_id = 't1') \
+         EXCEPT SELECT id FROM c WHERE (delete_flag = 0 AND tenant_id = 't1')"
+    );
+}
+
+#[test]
+fn statements_starting_with_comment_or_parenthesis_are_rewritten() {
+    let hook = tenant_hook();
+    let sql = rewrite(&hook, DatabaseBackend::MySql, "/* list */ SELECT * FROM a");
+    assert_eq!(sql, "SELECT * FROM a WHERE (delete_flag = 0 AND tenant_id = 't1')");
+
+    let sql = rewrite(&hook, DatabaseBackend::Postgres, "-- note\nWITH x AS (SELECT id FROM a) SELECT * FROM x");
+    assert_eq!(sql, "WITH x AS (SELECT id FROM a WHERE (delete_flag = 0 AND tenant_id = 't1')) SELECT * FROM x");
+
+    let sql = rewrite(&hook, DatabaseBackend::MySql, "(SELECT id FROM a) UNION ALL (SELECT id FROM b) ORDER BY id");
+    assert_eq!(
+        sql,
+        "(SELECT id FROM a WHERE (delete_flag = 0 AND tenant_id = 't1')) \
+         UNION ALL (SELECT id FROM b WHERE (delete_flag = 0 AND tenant_id = 't1')) ORDER BY id"
+    );
+}
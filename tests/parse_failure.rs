@@ -0,0 +1,72 @@
+//! 解析失败策略测试：内联与绑定参数两条路径一致计数，拒绝策略下任何改写失败都不执行原语句
+
+mod common;
+
+use auto_field_trait::extract_hook::{DefaultQueryHook, QueryHook};
+use auto_field_trait::{HookErrorKind, ParseFailurePolicy};
+use common::{set_context, tenant_context};
+use sea_orm::{DatabaseBackend, Statement, Value};
+
+fn hook_with_policy(policy: ParseFailurePolicy) -> DefaultQueryHook {
+    set_context(tenant_context());
+    DefaultQueryHook::new(true, true).with_parse_failure_policy(policy)
+}
+
+/// 未闭合的字符串字面量，在编号占位符的词法分析阶段即失败
+fn unterminated_literal() -> Statement {
+    Statement::from_sql_and_values(
+        DatabaseBackend::MySql,
+        "SELECT * FROM orders WHERE id = ? AND name = 'abc",
+        [Value::from(1)],
+    )
+}
+
+#[test]
+fn allow_executes_unparseable_statements_and_counts_them() {
+    let hook = hook_with_policy(ParseFailurePolicy::Allow);
+
+    assert_eq!(hook.before_statement(&unterminated_literal()).unwrap(), None);
+    assert_eq!(hook.parse_failure_count(), 1);
+
+    // 解析失败的语句不缓存，每次执行都计数
+    assert_eq!(hook.before_statement(&unterminated_literal()).unwrap(), None);
+    assert_eq!(hook.parse_failure_count(), 2);
+
+    assert_eq!(hook.before_query("SELECT * FROM orders WHERE", DatabaseBackend::MySql).unwrap(), None);
+    assert_eq!(hook.parse_failure_count(), 3);
+}
+
+#[test]
+fn audit_policy_counts_bound_tokenizer_failures() {
+    let hook = hook_with_policy(ParseFailurePolicy::AllowAndAudit);
+    assert_eq!(hook.before_statement(&unterminated_literal()).unwrap(), None);
+    assert_eq!(hook.parse_failure_count(), 1);
+}
+
+#[test]
+fn reject_fails_closed_on_bound_tokenizer_failures() {
+    let hook = hook_with_policy(ParseFailurePolicy::Reject);
+
+    let err = hook.before_statement(&unterminated_literal()).unwrap_err();
+    assert_eq!(HookErrorKind::of(&err), Some(HookErrorKind::ParseFailure));
+    assert_eq!(hook.parse_failure_count(), 1);
+
+    let stmt = Statement::from_sql_and_values(DatabaseBackend::Postgres, "SELECT * FROM orders WHERE name = 'abc", []);
+    let err = hook.before_statement(&stmt).unwrap_err();
+    assert_eq!(HookErrorKind::of(&err), Some(HookErrorKind::ParseFailure));
+    assert_eq!(hook.parse_failure_count(), 2);
+}
+
+#[test]
+fn reject_fails_closed_on_rewrite_errors() {
+    // 占位符多于参数时无法生成改写后的语句
+    let stmt = Statement::from_sql_and_values(DatabaseBackend::MySql, "SELECT * FROM orders WHERE id = ?", []);
+
+    let hook = hook_with_policy(ParseFailurePolicy::Allow);
+    assert_eq!(hook.before_statement(&stmt).unwrap(), None);
+
+    let hook = hook_with_policy(ParseFailurePolicy::Reject);
+    let err = hook.before_statement(&stmt).unwrap_err();
+    assert_eq!(HookErrorKind::of(&err), Some(HookErrorKind::ParseFailure));
+    assert!(err.to_string().contains("Missing parameter"));
+}
@@ -0,0 +1,158 @@
+//! 查询执行结果测试：语句类型、耗时及行数经 HookedConnection 传给 after_query
+
+mod common;
+
+use auto_field_trait::extract_hook::{DefaultQueryHook, HookedConnection, QueryHook, QueryOutcome, StatementKind};
+use auto_field_trait::HookChain;
+use common::{block_on, set_context, tenant_context, EmptyConnection};
+use parking_lot::Mutex;
+use sea_orm::{ConnectionTrait, Database, DatabaseBackend, DbErr, ExecResult, QueryResult, Statement};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// after_query 收到的执行结果
+#[derive(Debug, Clone, PartialEq)]
+struct Recorded {
+    final_sql: String,
+    kind: StatementKind,
+    elapsed: Duration,
+    rows_affected: Option<u64>,
+    rows_returned: Option<usize>,
+    ok: bool,
+}
+
+/// 记录每次执行结果的钩子
+#[derive(Default)]
+struct RecordingHook {
+    outcomes: Mutex<Vec<Recorded>>,
+}
+
+impl QueryHook for RecordingHook {
+    fn before_query(&self, _sql: &str, _backend: DatabaseBackend) -> Result<Option<String>, DbErr> {
+        Ok(None)
+    }
+
+    fn after_query(&self, outcome: &QueryOutcome) {
+        self.outcomes.lock().push(Recorded {
+            final_sql: outcome.final_sql().to_string(),
+            kind: outcome.kind(),
+            elapsed: outcome.elapsed,
+            rows_affected: outcome.rows_affected,
+            rows_returned: outcome.rows_returned,
+            ok: outcome.is_ok(),
+        });
+    }
+}
+
+/// 查询前等待固定时间的连接，其余行为与 `EmptyConnection` 相同
+struct SlowConnection(Duration);
+
+#[async_trait::async_trait]
+impl ConnectionTrait for SlowConnection {
+    fn get_database_backend(&self) -> DatabaseBackend {
+        EmptyConnection.get_database_backend()
+    }
+
+    async fn execute(&self, stmt: Statement) -> Result<ExecResult, DbErr> {
+        EmptyConnection.execute(stmt).await
+    }
+
+    async fn execute_unprepared(&self, sql: &str) -> Result<ExecResult, DbErr> {
+        EmptyConnection.execute_unprepared(sql).await
+    }
+
+    async fn query_one(&self, stmt: Statement) -> Result<Option<QueryResult>, DbErr> {
+        std::thread::sleep(self.0);
+        EmptyConnection.query_one(stmt).await
+    }
+
+    async fn query_all(&self, stmt: Statement) -> Result<Vec<QueryResult>, DbErr> {
+        std::thread::sleep(self.0);
+        EmptyConnection.query_all(stmt).await
+    }
+}
+
+/// 默认查询钩子改写后由记录钩子记录执行结果
+fn recording_connection<C: ConnectionTrait>(inner: C) -> (HookedConnection<C>, Arc<RecordingHook>) {
+    set_context(tenant_context());
+    let recorder = Arc::new(RecordingHook::default());
+    let chain = HookChain::new()
+        .with_hook(Arc::new(DefaultQueryHook::new(true, true)), 0)
+        .with_hook(recorder.clone(), 10);
+    (HookedConnection::new_async(inner, Arc::new(chain)), recorder)
+}
+
+#[test]
+fn outcome_reports_elapsed_rows_and_failures() {
+    let delay = Duration::from_millis(20);
+    let (conn, recorder) = recording_connection(SlowConnection(delay));
+    let select = Statement::from_string(DatabaseBackend::MySql, "SELECT * FROM orders");
+
+    let start = Instant::now();
+    block_on(async {
+        conn.query_all(select.clone()).await.unwrap();
+        conn.query_one(select).await.unwrap();
+        conn.execute_unprepared("DELETE FROM orders WHERE id = 1").await.unwrap_err();
+    });
+    let wall_time = start.elapsed();
+
+    let outcomes = recorder.outcomes.lock().clone();
+    assert_eq!(outcomes.len(), 3);
+    assert_eq!(outcomes[0].final_sql, "SELECT * FROM orders WHERE (delete_flag = 0 AND tenant_id = ?)");
+    assert_eq!(outcomes[0].kind, StatementKind::Select);
+    assert_eq!((outcomes[0].rows_affected, outcomes[0].rows_returned), (None, Some(0)));
+    assert_eq!((outcomes[1].rows_affected, outcomes[1].rows_returned), (None, Some(0)));
+    assert!(outcomes[0].ok && outcomes[1].ok);
+
+    // 执行失败时没有影响行数
+    assert_eq!(outcomes[2].kind, StatementKind::Delete);
+    assert_eq!((outcomes[2].rows_affected, outcomes[2].rows_returned), (None, None));
+    assert!(!outcomes[2].ok);
+
+    // 耗时包含连接的执行时间，不超过调用的总时间
+    assert!(outcomes[0].elapsed >= delay && outcomes[1].elapsed >= delay);
+    let total: Duration = outcomes.iter().map(|outcome| outcome.elapsed).sum();
+    assert!(total <= wall_time);
+}
+
+#[test]
+fn outcome_reports_rows_affected() {
+    block_on(async {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        db.execute_unprepared(
+            "CREATE TABLE orders (id INTEGER PRIMARY KEY, amount INTEGER, delete_flag INTEGER, tenant_id TEXT); \
+             INSERT INTO orders VALUES (1, 5, 0, 't1'), (2, 5, 0, 't1'), (3, 5, 0, 't2');",
+        )
+        .await
+        .unwrap();
+        let (conn, recorder) = recording_connection(db);
+
+        let update = Statement::from_string(DatabaseBackend::Sqlite, "UPDATE orders SET amount = 0");
+        assert_eq!(conn.execute(update).await.unwrap().rows_affected(), 3);
+        let outcome = recorder.outcomes.lock()[0].clone();
+        assert_eq!(outcome.kind, StatementKind::Update);
+        assert_eq!((outcome.rows_affected, outcome.rows_returned), (Some(3), None));
+    });
+}
+
+#[test]
+fn statement_kind_follows_the_statement_after_with() {
+    let kind = |backend, sql| StatementKind::of(sql, backend);
+    assert_eq!(
+        kind(DatabaseBackend::Postgres, "WITH x AS (SELECT id FROM b) UPDATE a SET name = 'n' WHERE id IN (SELECT id FROM x)"),
+        StatementKind::Update
+    );
+    assert_eq!(
+        kind(DatabaseBackend::Postgres, "WITH x AS (SELECT id FROM b) DELETE FROM a WHERE id IN (SELECT id FROM x)"),
+        StatementKind::Delete
+    );
+    assert_eq!(
+        kind(DatabaseBackend::Postgres, "WITH x AS (SELECT id FROM b) INSERT INTO a (id) SELECT id FROM x"),
+        StatementKind::Insert
+    );
+    assert_eq!(kind(DatabaseBackend::MySql, "WITH x AS (SELECT id FROM a) SELECT * FROM x"), StatementKind::Select);
+    assert_eq!(kind(DatabaseBackend::MySql, "/* list */ (SELECT id FROM a) UNION (SELECT id FROM b)"), StatementKind::Select);
+    assert_eq!(kind(DatabaseBackend::MySql, "REPLACE INTO a (id) VALUES (?)"), StatementKind::Insert);
+    assert_eq!(kind(DatabaseBackend::MySql, "SHOW TABLES"), StatementKind::Select);
+    assert_eq!(kind(DatabaseBackend::Postgres, "CREATE TABLE t (id INT)"), StatementKind::Other);
+}
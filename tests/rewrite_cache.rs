@@ -0,0 +1,58 @@
+//! 带绑定参数语句的改写缓存测试：命中统计及配置变更后的失效
+
+mod common;
+
+use auto_field_trait::config::FilterColumnConfig;
+use auto_field_trait::extract_hook::{DefaultQueryHook, QueryHook};
+use common::{set_context, tenant_context};
+use sea_orm::{DatabaseBackend, Statement};
+
+fn select_orders() -> Statement {
+    Statement::from_sql_and_values(DatabaseBackend::MySql, "SELECT * FROM orders WHERE id = ?", [1.into()])
+}
+
+fn rewritten_sql(hook: &DefaultQueryHook) -> Option<String> {
+    hook.before_statement(&select_orders()).unwrap().map(|stmt| stmt.sql)
+}
+
+#[test]
+fn repeated_statements_hit_the_cache() {
+    set_context(tenant_context());
+    let hook = DefaultQueryHook::new(true, true);
+
+    let first = rewritten_sql(&hook);
+    let second = rewritten_sql(&hook);
+    assert_eq!(first, second);
+    assert_eq!(first.as_deref(), Some("SELECT * FROM orders WHERE id = ? AND (delete_flag = 0 AND tenant_id = ?)"));
+
+    let stats = hook.rewrite_cache_stats();
+    assert_eq!((stats.misses, stats.hits, stats.size), (1, 1, 1));
+}
+
+#[test]
+fn table_config_changes_invalidate_cached_templates() {
+    set_context(tenant_context());
+    let hook = DefaultQueryHook::new(true, true);
+    let shared = hook.clone();
+    assert!(rewritten_sql(&hook).is_some());
+
+    // 通过共享配置的克隆修改，原钩子不再使用旧模板
+    shared.add_skip_table("orders");
+    assert_eq!(rewritten_sql(&hook), None);
+
+    shared.remove_skip_table("orders");
+    shared.set_table_filter_columns(
+        "orders",
+        FilterColumnConfig {
+            tenant_column: Some("org_id".to_string()),
+            ..Default::default()
+        },
+    );
+    assert_eq!(
+        rewritten_sql(&hook).as_deref(),
+        Some("SELECT * FROM orders WHERE id = ? AND (delete_flag = 0 AND org_id = ?)")
+    );
+
+    let stats = hook.rewrite_cache_stats();
+    assert_eq!((stats.misses, stats.hits), (3, 0));
+}
@@ -0,0 +1,60 @@
+//! 表结构注册表测试：按实体登记字段，缺少的字段不添加对应的过滤条件
+
+mod common;
+
+use auto_field_trait::extract_hook::DefaultQueryHook;
+use common::{rewrite, set_context, tenant_context};
+use sea_orm::DatabaseBackend;
+
+/// 只有软删除字段、没有租户字段的实体
+mod region {
+    use sea_orm::entity::prelude::*;
+
+    #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+    #[sea_orm(table_name = "region")]
+    pub struct Model {
+        #[sea_orm(primary_key)]
+        pub id: i64,
+        pub name: String,
+        pub delete_flag: i32,
+    }
+
+    #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+    pub enum Relation {}
+
+    impl ActiveModelBehavior for ActiveModel {}
+}
+
+#[test]
+fn registered_entity_columns_limit_filters() {
+    set_context(tenant_context());
+    let hook = DefaultQueryHook::new(true, true);
+    hook.schema_registry().register_entity::<region::Entity>();
+
+    assert_eq!(
+        rewrite(&hook, DatabaseBackend::MySql, "SELECT * FROM region"),
+        "SELECT * FROM region WHERE delete_flag = 0"
+    );
+    // 未登记的表视为拥有全部字段
+    assert_eq!(
+        rewrite(&hook, DatabaseBackend::MySql, "SELECT * FROM orders"),
+        "SELECT * FROM orders WHERE (delete_flag = 0 AND tenant_id = 't1')"
+    );
+}
+
+#[test]
+fn table_without_tenant_column_gets_only_soft_delete_predicate() {
+    set_context(tenant_context());
+    let hook = DefaultQueryHook::new(true, true);
+    hook.schema_registry().register_table("dict", ["id", "code", "delete_flag"]);
+
+    assert_eq!(
+        rewrite(&hook, DatabaseBackend::MySql, "SELECT * FROM dict d JOIN orders o ON o.dict_id = d.id"),
+        "SELECT * FROM dict d JOIN orders o ON o.dict_id = d.id \
+         WHERE (d.delete_flag = 0 AND o.delete_flag = 0 AND o.tenant_id = 't1')"
+    );
+
+    // 重新登记后立即生效，不命中之前缓存的改写结果
+    hook.schema_registry().register_table("dict", ["id", "code"]);
+    assert_eq!(rewrite(&hook, DatabaseBackend::MySql, "SELECT * FROM dict"), "SELECT * FROM dict");
+}
@@ -0,0 +1,94 @@
+//! 慢查询日志钩子测试：低于阈值的语句不记录，慢查询记录租户、用户、最终 SQL 及耗时
+
+mod common;
+
+use auto_field_trait::extract_hook::{QueryHook, QueryOutcome};
+use auto_field_trait::{AutoFieldContext, SlowQueryHook};
+use common::tenant_context;
+use parking_lot::Mutex;
+use sea_orm::{DatabaseBackend, DbErr};
+use std::sync::Once;
+use std::time::Duration;
+
+/// 收集慢查询日志的记录器
+struct SlowQueryLogger;
+
+static RECORDS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+static INIT: Once = Once::new();
+
+impl log::Log for SlowQueryLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.target().ends_with("slow_query_hook")
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            RECORDS.lock().push(record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// 安装记录器，各测试共用
+fn init_logger() {
+    INIT.call_once(|| {
+        log::set_logger(&SlowQueryLogger).unwrap();
+        log::set_max_level(log::LevelFilter::Warn);
+    });
+}
+
+/// 包含 `pattern` 的日志
+fn records(pattern: &str) -> Vec<String> {
+    RECORDS.lock().iter().filter(|record| record.contains(pattern)).cloned().collect()
+}
+
+fn outcome<'a>(sql: &'a str, rewritten_sql: Option<&'a str>, elapsed_ms: u64, context: AutoFieldContext) -> QueryOutcome<'a> {
+    QueryOutcome {
+        original_sql: sql,
+        rewritten_sql,
+        elapsed: Duration::from_millis(elapsed_ms),
+        rows_affected: None,
+        rows_returned: Some(0),
+        backend: DatabaseBackend::MySql,
+        context,
+        result: Ok(()),
+    }
+}
+
+#[test]
+fn statements_under_threshold_are_not_logged() {
+    init_logger();
+    let hook = SlowQueryHook::from_millis(100);
+    hook.after_query(&outcome("SELECT * FROM fast_orders", None, 99, tenant_context()));
+    assert!(records("fast_orders").is_empty());
+}
+
+#[test]
+fn slow_statements_are_logged_with_context_and_final_sql() {
+    init_logger();
+    let hook = SlowQueryHook::from_millis(100);
+    hook.after_query(&outcome(
+        "SELECT * FROM slow_orders",
+        Some("SELECT * FROM slow_orders WHERE (delete_flag = 0 AND tenant_id = ?)"),
+        150,
+        tenant_context(),
+    ));
+    assert_eq!(
+        records("slow_orders"),
+        ["Slow query (MySql, 150 ms, tenant: t1, user: alice): \
+          SELECT * FROM slow_orders WHERE (delete_flag = 0 AND tenant_id = ?)"]
+    );
+
+    // 失败的语句同样记录错误，上下文缺少租户及用户时以 - 代替
+    let err = DbErr::Custom("timeout".to_string());
+    hook.after_query(&QueryOutcome {
+        result: Err(&err),
+        ..outcome("SELECT * FROM slow_items", None, 100, AutoFieldContext::default())
+    });
+    assert_eq!(
+        records("slow_items"),
+        ["Slow query (MySql, 100 ms, tenant: -, user: -): SELECT * FROM slow_items, error: Custom Error: timeout"]
+    );
+}
@@ -0,0 +1,130 @@
+//! 按表生效的软删除配置、其条件生成函数及改写结果的测试
+
+mod common;
+
+use auto_field_trait::config::{FilterColumnConfig, SoftDeleteStrategy};
+use auto_field_trait::extract_hook::{DefaultQueryHook, QueryHook};
+use common::{rewrite, set_context, tenant_context, use_fixed_clock};
+use sea_orm::prelude::DateTime;
+use sea_orm::sea_query::{Alias, MysqlQueryBuilder, Query, SimpleExpr};
+use sea_orm::{DatabaseBackend, Statement, Value};
+
+fn select_where(table: &str, condition: SimpleExpr) -> String {
+    Query::select()
+        .column(Alias::new("id"))
+        .from(Alias::new(table))
+        .and_where(condition)
+        .to_string(MysqlQueryBuilder)
+}
+
+#[test]
+fn table_overrides_take_precedence() {
+    let hook = DefaultQueryHook::new(true, false);
+    hook.set_table_filter_columns(
+        "legacy",
+        FilterColumnConfig {
+            soft_delete_strategy: Some(SoftDeleteStrategy::Timestamp),
+            soft_delete_column: Some("deleted_at".to_string()),
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(hook.soft_delete_strategy("orders"), SoftDeleteStrategy::Flag);
+    assert_eq!(hook.soft_delete_column("orders"), "delete_flag");
+    assert_eq!(
+        select_where("orders", hook.not_deleted_condition("orders")),
+        "SELECT `id` FROM `orders` WHERE `orders`.`delete_flag` = 0"
+    );
+
+    assert_eq!(hook.soft_delete_strategy("LEGACY"), SoftDeleteStrategy::Timestamp);
+    assert_eq!(hook.soft_delete_column("legacy"), "deleted_at");
+    assert_eq!(
+        select_where("legacy", hook.not_deleted_condition("legacy")),
+        "SELECT `id` FROM `legacy` WHERE `legacy`.`deleted_at` IS NULL"
+    );
+}
+
+#[test]
+fn boolean_strategy_uses_configured_values() {
+    let hook = DefaultQueryHook::new(true, false);
+    hook.set_filter_columns(FilterColumnConfig {
+        soft_delete_strategy: Some(SoftDeleteStrategy::Boolean),
+        soft_delete_column: Some("is_deleted".to_string()),
+        ..Default::default()
+    });
+    assert_eq!(
+        select_where("orders", hook.not_deleted_condition("orders")),
+        "SELECT `id` FROM `orders` WHERE `orders`.`is_deleted` = FALSE"
+    );
+    assert_eq!(
+        Query::update()
+            .table(Alias::new("orders"))
+            .value(Alias::new(hook.soft_delete_column("orders")), hook.deleted_value_expr("orders"))
+            .to_string(MysqlQueryBuilder),
+        "UPDATE `orders` SET `is_deleted` = TRUE"
+    );
+}
+
+#[test]
+fn free_functions_fall_back_to_default_columns() {
+    assert_eq!(auto_field_trait::soft_delete_column("orders"), "delete_flag");
+    assert_eq!(
+        select_where("orders", auto_field_trait::not_deleted_condition("orders")),
+        "SELECT `id` FROM `orders` WHERE `orders`.`delete_flag` = 0"
+    );
+}
+
+/// 开启 DELETE 过滤及软删除改写，全局使用 `strategy` 策略及 `column` 字段
+fn strategy_hook(strategy: SoftDeleteStrategy, column: &str) -> DefaultQueryHook {
+    set_context(tenant_context());
+    use_fixed_clock();
+    let hook = DefaultQueryHook::new(true, true)
+        .with_delete_filter(true)
+        .with_soft_delete_rewrite(true);
+    hook.set_filter_columns(FilterColumnConfig {
+        soft_delete_strategy: Some(strategy),
+        soft_delete_column: Some(column.to_string()),
+        ..Default::default()
+    });
+    hook
+}
+
+#[test]
+fn timestamp_strategy_filters_null_and_writes_current_time() {
+    let hook = strategy_hook(SoftDeleteStrategy::Timestamp, "deleted_at");
+    assert_eq!(
+        rewrite(&hook, DatabaseBackend::MySql, "SELECT * FROM orders"),
+        "SELECT * FROM orders WHERE (deleted_at IS NULL AND tenant_id = 't1')"
+    );
+    assert_eq!(
+        rewrite(&hook, DatabaseBackend::MySql, "DELETE FROM orders WHERE id = 1"),
+        "UPDATE orders SET deleted_at = '2024-01-02 03:04:05.000000', update_time = '2024-01-02 03:04:05.000000', \
+         update_by = 'alice' WHERE id = 1 AND (deleted_at IS NULL AND tenant_id = 't1')"
+    );
+
+    let stmt = Statement::from_sql_and_values(DatabaseBackend::Postgres, "DELETE FROM orders WHERE id = $1", [Value::from(1)]);
+    let rewritten = hook.before_statement(&stmt).unwrap().expect("statement was not rewritten");
+    assert_eq!(
+        rewritten.sql,
+        "UPDATE orders SET deleted_at = $2, update_time = $3, update_by = $4 WHERE id = $1 AND (deleted_at IS NULL AND tenant_id = $5)"
+    );
+    let now = DateTime::parse_from_str("2024-01-02 03:04:05", "%Y-%m-%d %H:%M:%S").unwrap();
+    assert_eq!(
+        rewritten.values.unwrap().0,
+        [Value::from(1), Value::from(now), Value::from(now), Value::from("alice"), Value::from("t1")]
+    );
+}
+
+#[test]
+fn boolean_strategy_filters_false_and_writes_true() {
+    let hook = strategy_hook(SoftDeleteStrategy::Boolean, "is_deleted");
+    assert_eq!(
+        rewrite(&hook, DatabaseBackend::Postgres, "SELECT * FROM orders"),
+        "SELECT * FROM orders WHERE (is_deleted = false AND tenant_id = 't1')"
+    );
+    assert_eq!(
+        rewrite(&hook, DatabaseBackend::Postgres, "DELETE FROM orders WHERE id = 1"),
+        "UPDATE orders SET is_deleted = true, update_time = '2024-01-02 03:04:05.000000', update_by = 'alice' \
+         WHERE id = 1 AND (is_deleted = false AND tenant_id = 't1')"
+    );
+}
@@ -0,0 +1,77 @@
+//! 流式查询测试：流式执行的语句同样添加租户及软删除条件，并保留原有的绑定参数
+
+mod common;
+
+use auto_field_trait::extract_hook::{DefaultQueryHook, HookedConnection};
+use common::{block_on, set_context, tenant_context, EmptyConnection};
+use futures_util::stream::{self, Empty};
+use futures_util::StreamExt;
+use parking_lot::Mutex;
+use sea_orm::{ConnectionTrait, DatabaseBackend, DbErr, ExecResult, QueryResult, Statement, StreamTrait, Value};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// 记录流式执行的语句、不返回任何行的连接
+#[derive(Default)]
+struct StreamConnection {
+    streamed: Arc<Mutex<Vec<Statement>>>,
+}
+
+#[async_trait::async_trait]
+impl ConnectionTrait for StreamConnection {
+    fn get_database_backend(&self) -> DatabaseBackend {
+        DatabaseBackend::Postgres
+    }
+
+    async fn execute(&self, stmt: Statement) -> Result<ExecResult, DbErr> {
+        EmptyConnection.execute(stmt).await
+    }
+
+    async fn execute_unprepared(&self, sql: &str) -> Result<ExecResult, DbErr> {
+        EmptyConnection.execute_unprepared(sql).await
+    }
+
+    async fn query_one(&self, stmt: Statement) -> Result<Option<QueryResult>, DbErr> {
+        EmptyConnection.query_one(stmt).await
+    }
+
+    async fn query_all(&self, stmt: Statement) -> Result<Vec<QueryResult>, DbErr> {
+        EmptyConnection.query_all(stmt).await
+    }
+}
+
+impl StreamTrait for StreamConnection {
+    type Stream<'a> = Empty<Result<QueryResult, DbErr>>;
+
+    fn stream<'a>(&'a self, stmt: Statement) -> Pin<Box<dyn Future<Output = Result<Self::Stream<'a>, DbErr>> + 'a + Send>> {
+        self.streamed.lock().push(stmt);
+        Box::pin(async { Ok(stream::empty()) })
+    }
+}
+
+#[test]
+fn streamed_statements_are_filtered_and_keep_bound_values() {
+    set_context(tenant_context());
+    let inner = StreamConnection::default();
+    let streamed = inner.streamed.clone();
+    let conn = HookedConnection::new(inner, Arc::new(DefaultQueryHook::new(true, true)));
+    let stmt = Statement::from_sql_and_values(
+        DatabaseBackend::Postgres,
+        "SELECT * FROM orders WHERE amount > $1 AND name = $2",
+        [Value::from(10), Value::from("n")],
+    );
+    let rows = block_on(async { conn.stream(stmt).await.unwrap().count().await });
+    assert_eq!(rows, 0);
+
+    let streamed = streamed.lock().clone();
+    assert_eq!(streamed.len(), 1);
+    assert_eq!(
+        streamed[0].sql,
+        "SELECT * FROM orders WHERE amount > $1 AND name = $2 AND (delete_flag = 0 AND tenant_id = $3)"
+    );
+    assert_eq!(
+        streamed[0].values.as_ref().unwrap().0,
+        [Value::from(10), Value::from("n"), Value::from("t1")]
+    );
+}
@@ -0,0 +1,80 @@
+//! 严格租户模式测试：缺少租户时拒绝语句，系统身份、跳过的表及没有租户字段的表除外
+
+mod common;
+
+use auto_field_trait::extract_hook::{DefaultQueryHook, QueryHook};
+use auto_field_trait::{system_scope, without_filters, AutoFieldContext, FilterKind, HookErrorKind};
+use common::{block_on, rewrite, set_context, tenant_context};
+use sea_orm::{DatabaseBackend, Statement};
+
+/// 开启软删除、租户过滤及严格租户模式，上下文只有用户没有租户
+fn strict_hook_without_tenant() -> DefaultQueryHook {
+    set_context(AutoFieldContext::default().with_user(Some("u1".to_string()), Some("alice".to_string()), None));
+    DefaultQueryHook::new(true, true).with_strict_tenant(true)
+}
+
+#[test]
+fn missing_tenant_is_rejected() {
+    let hook = strict_hook_without_tenant();
+
+    let err = hook.before_query("SELECT * FROM orders", DatabaseBackend::MySql).unwrap_err();
+    assert_eq!(HookErrorKind::of(&err), Some(HookErrorKind::MissingTenant));
+    assert!(err.to_string().contains("table: orders"));
+
+    let stmt = Statement::from_sql_and_values(DatabaseBackend::Postgres, "DELETE FROM orders WHERE id = $1", [1.into()]);
+    let err = hook.with_delete_filter(true).before_statement(&stmt).unwrap_err();
+    assert_eq!(HookErrorKind::of(&err), Some(HookErrorKind::MissingTenant));
+
+    // 子查询中的表同样需要租户
+    let hook = strict_hook_without_tenant();
+    let err = hook
+        .before_query("SELECT * FROM dict WHERE id IN (SELECT dict_id FROM orders)", DatabaseBackend::MySql)
+        .unwrap_err();
+    assert!(err.to_string().contains("table: orders"));
+}
+
+#[test]
+fn present_tenant_is_filtered_normally() {
+    set_context(tenant_context());
+    let hook = DefaultQueryHook::new(true, true).with_strict_tenant(true);
+    let sql = rewrite(&hook, DatabaseBackend::MySql, "SELECT * FROM orders");
+    assert_eq!(sql, "SELECT * FROM orders WHERE (delete_flag = 0 AND tenant_id = 't1')");
+}
+
+#[test]
+fn system_scope_allows_missing_tenant() {
+    let hook = strict_hook_without_tenant();
+    let sql = block_on(system_scope(async { rewrite(&hook, DatabaseBackend::MySql, "SELECT * FROM orders") }));
+    assert_eq!(sql, "SELECT * FROM orders WHERE delete_flag = 0");
+
+    // 作用域外仍然拒绝
+    assert!(hook.before_query("SELECT * FROM orders", DatabaseBackend::MySql).is_err());
+}
+
+#[test]
+fn disabled_tenant_filter_allows_missing_tenant() {
+    let hook = strict_hook_without_tenant();
+    let sql = block_on(without_filters(FilterKind::Tenant, async {
+        rewrite(&hook, DatabaseBackend::MySql, "SELECT * FROM orders")
+    }));
+    assert_eq!(sql, "SELECT * FROM orders WHERE delete_flag = 0");
+}
+
+#[test]
+fn skipped_and_tenantless_tables_allow_missing_tenant() {
+    let hook = strict_hook_without_tenant();
+    hook.add_skip_table("dict");
+    hook.schema_registry().register_table("region", ["id", "name", "delete_flag"]);
+
+    assert_eq!(rewrite(&hook, DatabaseBackend::MySql, "SELECT * FROM dict"), "SELECT * FROM dict");
+    assert_eq!(
+        rewrite(&hook, DatabaseBackend::MySql, "SELECT * FROM region"),
+        "SELECT * FROM region WHERE delete_flag = 0"
+    );
+
+    // 与需要租户的表关联时仍然拒绝
+    let err = hook
+        .before_query("SELECT * FROM dict JOIN orders ON orders.dict_id = dict.id", DatabaseBackend::MySql)
+        .unwrap_err();
+    assert_eq!(HookErrorKind::of(&err), Some(HookErrorKind::MissingTenant));
+}
@@ -0,0 +1,139 @@
+//! 租户层级测试：闭包表以 IN 列表展开下级租户，递归 CTE 以子查询注入，绑定参数按出现顺序排列
+
+mod common;
+
+use auto_field_trait::extract_hook::{DefaultQueryHook, QueryHook};
+use auto_field_trait::{ClosureTableHierarchy, RecursiveCteHierarchy};
+use common::{rewrite, set_context, tenant_context};
+use sea_orm::{DatabaseBackend, Statement, Value};
+use std::sync::Arc;
+
+/// t1 的下级租户为 t11、t12
+fn closure_table_hook() -> DefaultQueryHook {
+    set_context(tenant_context());
+    let hierarchy = ClosureTableHierarchy::new();
+    hierarchy.set_descendants("t1", vec!["t11".to_string(), "t12".to_string()]);
+    DefaultQueryHook::new(true, true).with_tenant_hierarchy(Arc::new(hierarchy))
+}
+
+/// 当前租户为 t1，另可见 t2，下级租户由 sys_tenant.parent_id 递归查询
+fn recursive_cte_hook() -> DefaultQueryHook {
+    set_context(tenant_context().with_tenant_ids(Some(vec!["t2".to_string()])));
+    DefaultQueryHook::new(true, true).with_tenant_hierarchy(Arc::new(RecursiveCteHierarchy::new(
+        "sys_tenant",
+        "id",
+        "parent_id",
+    )))
+}
+
+/// 递归查询 `parent` 的下级租户的子查询
+fn descendants_of(parent: &str) -> String {
+    format!(
+        "WITH RECURSIVE tenant_tree (id) AS (SELECT id FROM sys_tenant WHERE parent_id = {} \
+         UNION ALL SELECT child.id FROM sys_tenant child JOIN tenant_tree ON child.parent_id = tenant_tree.id) \
+         SELECT id FROM tenant_tree",
+        parent
+    )
+}
+
+#[test]
+fn closure_table_expands_descendants_into_in_list() {
+    let hook = closure_table_hook();
+    let sql = rewrite(&hook, DatabaseBackend::MySql, "SELECT * FROM orders");
+    assert_eq!(sql, "SELECT * FROM orders WHERE (delete_flag = 0 AND tenant_id IN ('t1', 't11', 't12'))");
+
+    // 没有下级租户的租户不受影响
+    set_context(tenant_context().with_tenant(Some("t2".to_string()), None));
+    let sql = rewrite(&hook, DatabaseBackend::MySql, "SELECT * FROM orders");
+    assert_eq!(sql, "SELECT * FROM orders WHERE (delete_flag = 0 AND tenant_id = 't2')");
+}
+
+#[test]
+fn closure_table_parameters_follow_placeholder_order() {
+    let hook = closure_table_hook();
+
+    // MySQL 的位置参数按出现顺序排列，子查询中新增的参数位于原参数之前
+    let stmt = Statement::from_sql_and_values(
+        DatabaseBackend::MySql,
+        "SELECT * FROM orders WHERE id IN (SELECT order_id FROM items) AND name = ?",
+        [Value::from("n")],
+    );
+    let rewritten = hook.before_statement(&stmt).unwrap().expect("statement was not rewritten");
+    assert_eq!(
+        rewritten.sql,
+        "SELECT * FROM orders WHERE id IN (SELECT order_id FROM items WHERE (delete_flag = 0 AND tenant_id IN (?, ?, ?))) \
+         AND name = ? AND (delete_flag = 0 AND tenant_id IN (?, ?, ?))"
+    );
+    let tenants = [Value::from("t1"), Value::from("t11"), Value::from("t12")];
+    let expected: Vec<Value> = tenants.iter().cloned().chain([Value::from("n")]).chain(tenants.iter().cloned()).collect();
+    assert_eq!(rewritten.values.unwrap().0, expected);
+
+    // PostgreSQL 的编号参数追加在原参数之后
+    let stmt = Statement::from_sql_and_values(DatabaseBackend::Postgres, "SELECT * FROM orders WHERE name = $1", [Value::from("n")]);
+    let rewritten = hook.before_statement(&stmt).unwrap().expect("statement was not rewritten");
+    assert_eq!(rewritten.sql, "SELECT * FROM orders WHERE name = $1 AND (delete_flag = 0 AND tenant_id IN ($2, $3, $4))");
+    assert_eq!(rewritten.values.unwrap().0[1..], tenants);
+}
+
+#[test]
+fn recursive_cte_is_injected_as_subquery_per_visible_tenant() {
+    let hook = recursive_cte_hook();
+    let sql = rewrite(&hook, DatabaseBackend::Postgres, "SELECT * FROM orders");
+    assert_eq!(
+        sql,
+        format!(
+            "SELECT * FROM orders WHERE (delete_flag = 0 AND (tenant_id IN ('t1', 't2') \
+             OR tenant_id IN ({}) OR tenant_id IN ({})))",
+            descendants_of("'t1'"),
+            descendants_of("'t2'")
+        )
+    );
+}
+
+#[test]
+fn recursive_cte_parameters_follow_placeholder_order() {
+    let hook = recursive_cte_hook();
+    let expected_values = [
+        Value::from(1),
+        Value::from("n"),
+        Value::from("t1"),
+        Value::from("t2"),
+        Value::from("t1"),
+        Value::from("t2"),
+    ];
+
+    let stmt = Statement::from_sql_and_values(
+        DatabaseBackend::MySql,
+        "SELECT * FROM orders WHERE id = ? AND name = ?",
+        [Value::from(1), Value::from("n")],
+    );
+    let rewritten = hook.before_statement(&stmt).unwrap().expect("statement was not rewritten");
+    assert_eq!(
+        rewritten.sql,
+        format!(
+            "SELECT * FROM orders WHERE id = ? AND name = ? AND (delete_flag = 0 AND (tenant_id IN (?, ?) \
+             OR tenant_id IN ({}) OR tenant_id IN ({})))",
+            descendants_of("?"),
+            descendants_of("?")
+        )
+    );
+    assert_eq!(rewritten.values.unwrap().0, expected_values);
+
+    // 子查询的参数先于租户条件绑定，编号不连续但取值与编号一一对应
+    let stmt = Statement::from_sql_and_values(
+        DatabaseBackend::Postgres,
+        "SELECT * FROM orders WHERE id = $1 AND name = $2",
+        [Value::from(1), Value::from("n")],
+    );
+    let rewritten = hook.before_statement(&stmt).unwrap().expect("statement was not rewritten");
+    assert_eq!(
+        rewritten.sql,
+        format!(
+            "SELECT * FROM orders WHERE id = $1 AND name = $2 AND (delete_flag = 0 AND (tenant_id IN ($5, $6) \
+             OR tenant_id IN ({}) OR tenant_id IN ({})))",
+            descendants_of("$3"),
+            descendants_of("$4")
+        )
+    );
+    assert_eq!(rewritten.values.unwrap().0, expected_values);
+}
@@ -0,0 +1,112 @@
+//! 多租户可见范围测试：租户集合以 IN 列表过滤，共享行只在查询时可见
+
+mod common;
+
+use auto_field_trait::config::{FilterColumnConfig, FilterValue};
+use auto_field_trait::extract_hook::{DefaultQueryHook, QueryHook};
+use common::{rewrite, set_context, tenant_context};
+use sea_orm::{DatabaseBackend, Statement, Value};
+
+/// 当前租户为 t1，另可见 t2、t3（含重复及空值）
+fn tenant_set_hook() -> DefaultQueryHook {
+    set_context(tenant_context().with_tenant_ids(Some(vec![
+        "t2".to_string(),
+        "t1".to_string(),
+        String::new(),
+        "t3".to_string(),
+    ])));
+    DefaultQueryHook::new(true, true)
+}
+
+/// 租户字段为 NULL 或 '0' 的行为共享行
+fn shared_rows_hook() -> DefaultQueryHook {
+    set_context(tenant_context());
+    let hook = DefaultQueryHook::new(true, true).with_update_filter(true).with_delete_filter(true);
+    hook.set_filter_columns(FilterColumnConfig {
+        shared_tenant_values: Some(vec![FilterValue::Text("0".to_string())]),
+        shared_tenant_null: Some(true),
+        ..Default::default()
+    });
+    hook
+}
+
+#[test]
+fn tenant_set_is_filtered_with_in_list() {
+    let hook = tenant_set_hook();
+    let sql = rewrite(&hook, DatabaseBackend::MySql, "SELECT * FROM orders o JOIN items i ON i.order_id = o.id");
+    assert_eq!(
+        sql,
+        "SELECT * FROM orders o JOIN items i ON i.order_id = o.id WHERE (o.delete_flag = 0 AND o.tenant_id IN ('t1', 't2', 't3') \
+         AND i.delete_flag = 0 AND i.tenant_id IN ('t1', 't2', 't3'))"
+    );
+}
+
+#[test]
+fn tenant_set_is_bound_in_order() {
+    let hook = tenant_set_hook();
+    let stmt = Statement::from_sql_and_values(DatabaseBackend::MySql, "SELECT * FROM orders WHERE id = ?", [Value::from(1)]);
+    let rewritten = hook.before_statement(&stmt).unwrap().expect("statement was not rewritten");
+    assert_eq!(rewritten.sql, "SELECT * FROM orders WHERE id = ? AND (delete_flag = 0 AND tenant_id IN (?, ?, ?))");
+    assert_eq!(
+        rewritten.values.unwrap().0,
+        [Value::from(1), Value::from("t1"), Value::from("t2"), Value::from("t3")]
+    );
+
+    // 可见租户数量相同时命中缓存，取值来自当前上下文
+    set_context(tenant_context().with_tenant_ids(Some(vec!["t4".to_string(), "t5".to_string()])));
+    let stmt = Statement::from_sql_and_values(DatabaseBackend::Postgres, "SELECT * FROM orders WHERE id = $1", [Value::from(1)]);
+    let first = hook.before_statement(&stmt).unwrap().expect("statement was not rewritten");
+    let second = hook.before_statement(&stmt).unwrap().expect("statement was not rewritten");
+    assert_eq!(first.sql, "SELECT * FROM orders WHERE id = $1 AND (delete_flag = 0 AND tenant_id IN ($2, $3, $4))");
+    assert_eq!(first.values, second.values);
+    assert_eq!(
+        second.values.unwrap().0,
+        [Value::from(1), Value::from("t1"), Value::from("t4"), Value::from("t5")]
+    );
+    assert_eq!(hook.rewrite_cache_stats().hits, 1);
+}
+
+#[test]
+fn shared_rows_are_visible_to_queries() {
+    let hook = shared_rows_hook();
+    let sql = rewrite(&hook, DatabaseBackend::MySql, "SELECT * FROM orders o LEFT JOIN items i ON i.order_id = o.id");
+    assert_eq!(
+        sql,
+        "SELECT * FROM orders o LEFT JOIN items i ON i.order_id = o.id \
+         AND (i.delete_flag = 0 AND (i.tenant_id = 't1' OR i.tenant_id IS NULL OR i.tenant_id = '0')) \
+         WHERE (o.delete_flag = 0 AND (o.tenant_id = 't1' OR o.tenant_id IS NULL OR o.tenant_id = '0'))"
+    );
+
+    // 租户集合与共享行组合
+    set_context(tenant_context().with_tenant_ids(Some(vec!["t2".to_string()])));
+    let sql = rewrite(&hook, DatabaseBackend::Postgres, "SELECT * FROM orders");
+    assert_eq!(
+        sql,
+        "SELECT * FROM orders WHERE (delete_flag = 0 AND (tenant_id IN ('t1', 't2') OR tenant_id IS NULL OR tenant_id = '0'))"
+    );
+}
+
+#[test]
+fn shared_rows_are_excluded_for_write_targets() {
+    let hook = shared_rows_hook();
+
+    // 子查询读取的表仍包含共享行，被修改的表只能修改本租户的行
+    let sql = rewrite(
+        &hook,
+        DatabaseBackend::MySql,
+        "UPDATE orders SET name = 'n' WHERE id IN (SELECT order_id FROM items)",
+    );
+    assert_eq!(
+        sql,
+        "UPDATE orders SET name = 'n' WHERE id IN (SELECT order_id FROM items \
+         WHERE (delete_flag = 0 AND (tenant_id = 't1' OR tenant_id IS NULL OR tenant_id = '0'))) \
+         AND (delete_flag = 0 AND tenant_id = 't1')"
+    );
+
+    let sql = rewrite(&hook, DatabaseBackend::MySql, "DELETE FROM orders WHERE id = 1");
+    assert_eq!(sql, "DELETE FROM orders WHERE id = 1 AND (delete_flag = 0 AND tenant_id = 't1')");
+
+    let stmt = Statement::from_sql_and_values(DatabaseBackend::Postgres, "DELETE FROM orders WHERE id = $1", [Value::from(1)]);
+    let rewritten = hook.before_statement(&stmt).unwrap().expect("statement was not rewritten");
+    assert_eq!(rewritten.sql, "DELETE FROM orders WHERE id = $1 AND (delete_flag = 0 AND tenant_id = $2)");
+}
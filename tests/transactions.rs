@@ -0,0 +1,84 @@
+//! 事务测试：事务及保存点上的语句经过钩子，关闭过滤条件的连接视图开启的事务同样关闭过滤
+
+mod common;
+
+use auto_field_trait::extract_hook::{DefaultQueryHook, HookedConnection};
+use common::{block_on, set_context, tenant_context};
+use sea_orm::{ConnectionTrait, Database, DatabaseBackend, DbConn, DbErr, Statement};
+use std::sync::Arc;
+
+/// t1 有一条未删除及一条已删除的行，t2 有一条未删除的行
+async fn orders_connection() -> HookedConnection<DbConn> {
+    set_context(tenant_context());
+    let db = Database::connect("sqlite::memory:").await.unwrap();
+    db.execute_unprepared(
+        "CREATE TABLE orders (id INTEGER PRIMARY KEY, delete_flag INTEGER NOT NULL, tenant_id TEXT NOT NULL); \
+         INSERT INTO orders (id, delete_flag, tenant_id) VALUES (1, 0, 't1'), (2, 0, 't2'), (3, 1, 't1');",
+    )
+    .await
+    .unwrap();
+    HookedConnection::new(db, Arc::new(DefaultQueryHook::new(true, true).with_delete_filter(true)))
+}
+
+/// 查询可见的订单ID
+async fn visible_ids<C: ConnectionTrait>(conn: &C) -> Vec<i64> {
+    let select = Statement::from_string(DatabaseBackend::Sqlite, "SELECT id FROM orders ORDER BY id");
+    conn.query_all(select)
+        .await
+        .unwrap()
+        .iter()
+        .map(|row| row.try_get::<i64>("", "id").unwrap())
+        .collect()
+}
+
+#[test]
+fn statements_in_transaction_go_through_the_hook() {
+    block_on(async {
+        let conn = orders_connection().await;
+        let transaction = conn.begin().await.unwrap();
+        assert_eq!(visible_ids(&transaction).await, [1]);
+
+        // DELETE 只删除本租户未删除的行
+        let deleted = transaction.execute_unprepared("DELETE FROM orders").await.unwrap();
+        assert_eq!(deleted.rows_affected(), 1);
+        transaction.rollback().await.unwrap();
+
+        let ids = conn
+            .transaction::<_, _, DbErr>(|transaction| Box::pin(async move { Ok(visible_ids(transaction).await) }))
+            .await
+            .unwrap();
+        assert_eq!(ids, [1]);
+    });
+}
+
+#[test]
+fn statements_in_savepoint_go_through_the_hook() {
+    block_on(async {
+        let conn = orders_connection().await;
+        let transaction = conn.begin().await.unwrap();
+        let savepoint = transaction.begin().await.unwrap();
+        assert_eq!(visible_ids(&savepoint).await, [1]);
+        savepoint.commit().await.unwrap();
+        transaction.commit().await.unwrap();
+    });
+}
+
+#[test]
+fn unfiltered_state_carries_into_transaction() {
+    block_on(async {
+        let conn = orders_connection().await;
+        let unfiltered = conn.unfiltered();
+        let transaction = unfiltered.begin().await.unwrap();
+        assert_eq!(visible_ids(&transaction).await, [1, 2, 3]);
+
+        let savepoint = transaction.begin().await.unwrap();
+        assert_eq!(visible_ids(&savepoint).await, [1, 2, 3]);
+        savepoint.rollback().await.unwrap();
+        transaction.rollback().await.unwrap();
+
+        // 原连接开启的事务仍然过滤
+        let transaction = conn.begin().await.unwrap();
+        assert_eq!(visible_ids(&transaction).await, [1]);
+        transaction.rollback().await.unwrap();
+    });
+}
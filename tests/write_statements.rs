@@ -0,0 +1,400 @@
+//! UPDATE、DELETE、INSERT 语句的改写测试：过滤条件、软删除改写及审计字段填充
+
+mod common;
+
+use auto_field_trait::extract_hook::{DefaultQueryHook, QueryHook};
+use auto_field_trait::hard_delete;
+use common::{block_on, rewrite, set_context, tenant_context, use_fixed_clock};
+use sea_orm::prelude::DateTime;
+use sea_orm::{DatabaseBackend, Statement, Value};
+
+/// 开启 UPDATE、DELETE 过滤条件
+fn guard_hook() -> DefaultQueryHook {
+    set_context(tenant_context());
+    DefaultQueryHook::new(true, true)
+        .with_update_filter(true)
+        .with_delete_filter(true)
+}
+
+#[test]
+fn update_and_delete_are_guarded() {
+    let hook = guard_hook();
+    let sql = rewrite(&hook, DatabaseBackend::MySql, "UPDATE orders SET amount = 0 WHERE id = 1");
+    assert_eq!(sql, "UPDATE orders SET amount = 0 WHERE id = 1 AND (delete_flag = 0 AND tenant_id = 't1')");
+
+    let sql = rewrite(&hook, DatabaseBackend::MySql, "DELETE FROM orders WHERE id = 1 OR id = 2");
+    assert_eq!(sql, "DELETE FROM orders WHERE (id = 1 OR id = 2) AND (delete_flag = 0 AND tenant_id = 't1')");
+}
+
+#[test]
+fn writes_are_untouched_when_guards_are_disabled() {
+    set_context(tenant_context());
+    let hook = DefaultQueryHook::new(true, true);
+    let sql = "UPDATE orders SET amount = 0 WHERE id = 1";
+    assert_eq!(rewrite(&hook, DatabaseBackend::MySql, sql), sql);
+    let sql = "DELETE FROM orders WHERE id = 1";
+    assert_eq!(rewrite(&hook, DatabaseBackend::MySql, sql), sql);
+}
+
+#[test]
+fn nested_queries_are_filtered_when_write_flags_are_off() {
+    set_context(tenant_context());
+    let hook = DefaultQueryHook::new(true, true);
+
+    // 只过滤嵌套查询，被写入的表不受影响
+    assert_eq!(
+        rewrite(&hook, DatabaseBackend::MySql, "INSERT INTO archive (id) SELECT id FROM orders"),
+        "INSERT INTO archive (id) SELECT id FROM orders WHERE (delete_flag = 0 AND tenant_id = 't1')"
+    );
+    assert_eq!(
+        rewrite(&hook, DatabaseBackend::MySql, "DELETE FROM archive WHERE id IN (SELECT id FROM orders)"),
+        "DELETE FROM archive WHERE id IN (SELECT id FROM orders WHERE (delete_flag = 0 AND tenant_id = 't1'))"
+    );
+    assert_eq!(
+        rewrite(&hook, DatabaseBackend::MySql, "UPDATE orders SET total = (SELECT SUM(x) FROM items)"),
+        "UPDATE orders SET total = (SELECT SUM(x) FROM items WHERE (delete_flag = 0 AND tenant_id = 't1'))"
+    );
+    assert_eq!(
+        rewrite(
+            &hook,
+            DatabaseBackend::MySql,
+            "INSERT INTO archive (id, total) VALUES (1, 0) ON DUPLICATE KEY UPDATE total = (SELECT SUM(x) FROM items)"
+        ),
+        "INSERT INTO archive (id, total) VALUES (1, 0) ON DUPLICATE KEY UPDATE total = (SELECT SUM(x) FROM items \
+         WHERE (delete_flag = 0 AND tenant_id = 't1'))"
+    );
+}
+
+#[test]
+fn update_subqueries_are_filtered() {
+    let sql = rewrite(
+        &guard_hook(),
+        DatabaseBackend::MySql,
+        "UPDATE orders SET amount = 0 WHERE user_id IN (SELECT id FROM users)",
+    );
+    assert_eq!(
+        sql,
+        "UPDATE orders SET amount = 0 WHERE user_id IN (SELECT id FROM users WHERE (delete_flag = 0 AND tenant_id = 't1')) \
+         AND (delete_flag = 0 AND tenant_id = 't1')"
+    );
+}
+
+#[test]
+fn mysql_multi_table_update_and_delete_guard_every_table() {
+    let hook = guard_hook();
+    let sql = rewrite(
+        &hook,
+        DatabaseBackend::MySql,
+        "UPDATE orders o JOIN users u ON u.id = o.user_id SET o.amount = 0 WHERE u.name = 'x'",
+    );
+    assert_eq!(
+        sql,
+        "UPDATE orders o JOIN users u ON u.id = o.user_id SET o.amount = 0 WHERE u.name = 'x' \
+         AND (o.delete_flag = 0 AND o.tenant_id = 't1' AND u.delete_flag = 0 AND u.tenant_id = 't1')"
+    );
+
+    let sql = rewrite(
+        &hook,
+        DatabaseBackend::MySql,
+        "DELETE o FROM orders o JOIN users u ON u.id = o.user_id WHERE u.name = 'x'",
+    );
+    assert_eq!(
+        sql,
+        "DELETE o FROM orders o JOIN users u ON u.id = o.user_id WHERE u.name = 'x' \
+         AND (o.delete_flag = 0 AND o.tenant_id = 't1' AND u.delete_flag = 0 AND u.tenant_id = 't1')"
+    );
+}
+
+#[test]
+fn update_from_and_delete_using_guard_every_table() {
+    let hook = guard_hook();
+    let sql = rewrite(
+        &hook,
+        DatabaseBackend::Postgres,
+        "UPDATE orders SET amount = 0 FROM users WHERE users.id = orders.user_id",
+    );
+    assert_eq!(
+        sql,
+        "UPDATE orders SET amount = 0 FROM users WHERE users.id = orders.user_id \
+         AND (orders.delete_flag = 0 AND orders.tenant_id = 't1' AND users.delete_flag = 0 AND users.tenant_id = 't1')"
+    );
+
+    let sql = rewrite(
+        &hook,
+        DatabaseBackend::Postgres,
+        "DELETE FROM orders USING users WHERE users.id = orders.user_id",
+    );
+    assert_eq!(
+        sql,
+        "DELETE FROM orders USING users WHERE users.id = orders.user_id \
+         AND (orders.delete_flag = 0 AND orders.tenant_id = 't1' AND users.delete_flag = 0 AND users.tenant_id = 't1')"
+    );
+}
+
+#[test]
+fn skipped_table_is_not_guarded() {
+    let hook = guard_hook();
+    hook.add_skip_table("sys_dict");
+    let sql = "UPDATE sys_dict SET label = 'x' WHERE code = 'a'";
+    assert_eq!(rewrite(&hook, DatabaseBackend::MySql, sql), sql);
+}
+
+/// 开启 DELETE 过滤及软删除改写
+fn soft_delete_rewrite_hook() -> DefaultQueryHook {
+    set_context(tenant_context());
+    use_fixed_clock();
+    DefaultQueryHook::new(true, true)
+        .with_delete_filter(true)
+        .with_soft_delete_rewrite(true)
+}
+
+#[test]
+fn delete_is_rewritten_to_soft_delete() {
+    let sql = rewrite(&soft_delete_rewrite_hook(), DatabaseBackend::MySql, "DELETE FROM orders WHERE id = 1");
+    assert_eq!(
+        sql,
+        "UPDATE orders SET delete_flag = 1, update_time = '2024-01-02 03:04:05.000000', update_by = 'alice' \
+         WHERE id = 1 AND (delete_flag = 0 AND tenant_id = 't1')"
+    );
+
+    let sql = rewrite(&soft_delete_rewrite_hook(), DatabaseBackend::Postgres, "DELETE FROM orders WHERE id = 1 RETURNING id");
+    assert_eq!(
+        sql,
+        "UPDATE orders SET delete_flag = 1, update_time = '2024-01-02 03:04:05.000000', update_by = 'alice' \
+         WHERE id = 1 AND (delete_flag = 0 AND tenant_id = 't1') RETURNING id"
+    );
+}
+
+#[test]
+fn delete_with_order_by_and_limit_keeps_them() {
+    let hook = soft_delete_rewrite_hook();
+    let sql = rewrite(
+        &hook,
+        DatabaseBackend::MySql,
+        "DELETE FROM orders WHERE amount < 1 ORDER BY create_time LIMIT 10",
+    );
+    assert_eq!(
+        sql,
+        "UPDATE orders SET delete_flag = 1, update_time = '2024-01-02 03:04:05.000000', update_by = 'alice' \
+         WHERE amount < 1 AND (delete_flag = 0 AND tenant_id = 't1') ORDER BY create_time LIMIT 10"
+    );
+
+    // 绑定参数按出现顺序排列，LIMIT 的参数仍在最后
+    let stmt = Statement::from_sql_and_values(
+        DatabaseBackend::MySql,
+        "DELETE FROM orders WHERE amount < ? ORDER BY create_time LIMIT ?",
+        [1.into(), 10.into()],
+    );
+    let rewritten = hook.before_statement(&stmt).unwrap().expect("statement was not rewritten");
+    assert_eq!(
+        rewritten.sql,
+        "UPDATE orders SET delete_flag = 1, update_time = ?, update_by = ? \
+         WHERE amount < ? AND (delete_flag = 0 AND tenant_id = ?) ORDER BY create_time LIMIT ?"
+    );
+    let values = rewritten.values.unwrap().0;
+    assert_eq!(values[1], Value::from("alice"));
+    assert_eq!(values[2], Value::from(1));
+    assert_eq!(values[3], Value::from("t1"));
+    assert_eq!(values[4], Value::from(10));
+}
+
+#[test]
+fn multi_table_and_skipped_deletes_stay_physical() {
+    let hook = soft_delete_rewrite_hook();
+    hook.add_skip_table("sys_log");
+    let sql = "DELETE FROM sys_log WHERE id = 1";
+    assert_eq!(rewrite(&hook, DatabaseBackend::MySql, sql), sql);
+
+    let sql = rewrite(&hook, DatabaseBackend::MySql, "DELETE o FROM orders o JOIN users u ON u.id = o.user_id");
+    assert_eq!(
+        sql,
+        "DELETE o FROM orders o JOIN users u ON u.id = o.user_id \
+         WHERE (o.delete_flag = 0 AND o.tenant_id = 't1' AND u.delete_flag = 0 AND u.tenant_id = 't1')"
+    );
+}
+
+#[test]
+fn hard_delete_scope_keeps_physical_delete() {
+    let hook = soft_delete_rewrite_hook();
+    let sql = block_on(hard_delete(async {
+        rewrite(&hook, DatabaseBackend::MySql, "DELETE FROM orders WHERE id = 1")
+    }));
+    assert_eq!(sql, "DELETE FROM orders WHERE id = 1 AND (delete_flag = 0 AND tenant_id = 't1')");
+}
+
+/// 开启 INSERT、UPDATE 字段填充，orders 包含版本号字段
+fn fill_hook() -> DefaultQueryHook {
+    set_context(tenant_context().with_tenant(Some("t1".to_string()), Some("acme".to_string())));
+    use_fixed_clock();
+    let hook = DefaultQueryHook::new(true, true)
+        .with_insert_fill(true)
+        .with_update_fill(true);
+    hook.add_version_table("orders");
+    hook
+}
+
+#[test]
+fn insert_fills_every_row() {
+    let sql = rewrite(&fill_hook(), DatabaseBackend::MySql, "INSERT INTO orders (id, amount) VALUES (1, 2), (3, 4)");
+    assert_eq!(
+        sql,
+        "INSERT INTO orders (id, amount, create_time, create_id, create_by, tenant_id, tenant_name) \
+         VALUES (1, 2, '2024-01-02 03:04:05.000000', 'u1', 'alice', 't1', 'acme'), \
+         (3, 4, '2024-01-02 03:04:05.000000', 'u1', 'alice', 't1', 'acme')"
+    );
+}
+
+#[test]
+fn with_insert_is_not_filled_when_insert_fill_is_off() {
+    set_context(tenant_context());
+    let hook = DefaultQueryHook::new(true, true);
+    let sql = "WITH x AS (SELECT 1 AS a) INSERT INTO orders (id) VALUES (1)";
+    assert_eq!(rewrite(&hook, DatabaseBackend::Postgres, sql), sql);
+
+    // CTE 中读取的表仍然过滤
+    let sql = rewrite(
+        &hook,
+        DatabaseBackend::Postgres,
+        "WITH x AS (SELECT id FROM items) INSERT INTO orders (id) SELECT id FROM x",
+    );
+    assert_eq!(
+        sql,
+        "WITH x AS (SELECT id FROM items WHERE (delete_flag = 0 AND tenant_id = 't1')) INSERT INTO orders (id) SELECT id FROM x"
+    );
+}
+
+#[test]
+fn insert_keeps_explicit_and_unregistered_columns_out() {
+    let hook = fill_hook();
+    let sql = rewrite(&hook, DatabaseBackend::MySql, "INSERT INTO orders (id, create_by) VALUES (1, 'bob')");
+    assert_eq!(
+        sql,
+        "INSERT INTO orders (id, create_by, create_time, create_id, tenant_id, tenant_name) \
+         VALUES (1, 'bob', '2024-01-02 03:04:05.000000', 'u1', 't1', 'acme')"
+    );
+
+    hook.schema_registry().register_table("items", ["id", "name", "create_time", "tenant_id"]);
+    let sql = rewrite(&hook, DatabaseBackend::MySql, "INSERT INTO items (id, name) VALUES (1, 'a')");
+    assert_eq!(
+        sql,
+        "INSERT INTO items (id, name, create_time, tenant_id) VALUES (1, 'a', '2024-01-02 03:04:05.000000', 't1')"
+    );
+}
+
+#[test]
+fn multi_row_insert_reuses_bound_placeholders() {
+    let hook = fill_hook();
+    let filled = [
+        Value::from(DateTime::parse_from_str("2024-01-02 03:04:05", "%Y-%m-%d %H:%M:%S").unwrap()),
+        Value::from("u1"),
+        Value::from("alice"),
+        Value::from("t1"),
+        Value::from("acme"),
+    ];
+
+    // MySQL 的位置占位符在每一行重复绑定同一组值
+    let stmt = Statement::from_sql_and_values(
+        DatabaseBackend::MySql,
+        "INSERT INTO orders (id, amount) VALUES (?, ?), (?, ?)",
+        [1.into(), 2.into(), 3.into(), 4.into()],
+    );
+    let rewritten = hook.before_statement(&stmt).unwrap().expect("statement was not rewritten");
+    assert_eq!(
+        rewritten.sql,
+        "INSERT INTO orders (id, amount, create_time, create_id, create_by, tenant_id, tenant_name) \
+         VALUES (?, ?, ?, ?, ?, ?, ?), (?, ?, ?, ?, ?, ?, ?)"
+    );
+    let mut expected = vec![Value::from(1), Value::from(2)];
+    expected.extend(filled.iter().cloned());
+    expected.extend([Value::from(3), Value::from(4)]);
+    expected.extend(filled.iter().cloned());
+    assert_eq!(rewritten.values.unwrap().0, expected);
+
+    // PostgreSQL 的编号占位符在每一行引用同一个参数
+    let stmt = Statement::from_sql_and_values(
+        DatabaseBackend::Postgres,
+        "INSERT INTO orders (id, amount) VALUES ($1, $2), ($3, $4)",
+        [1.into(), 2.into(), 3.into(), 4.into()],
+    );
+    let rewritten = hook.before_statement(&stmt).unwrap().expect("statement was not rewritten");
+    assert_eq!(
+        rewritten.sql,
+        "INSERT INTO orders (id, amount, create_time, create_id, create_by, tenant_id, tenant_name) \
+         VALUES ($1, $2, $5, $6, $7, $8, $9), ($3, $4, $5, $6, $7, $8, $9)"
+    );
+    let mut expected = vec![Value::from(1), Value::from(2), Value::from(3), Value::from(4)];
+    expected.extend(filled);
+    assert_eq!(rewritten.values.unwrap().0, expected);
+}
+
+#[test]
+fn update_fills_audit_columns_and_increments_version() {
+    let hook = fill_hook();
+    let sql = rewrite(&hook, DatabaseBackend::MySql, "UPDATE orders SET amount = 1 WHERE id = 1");
+    assert_eq!(
+        sql,
+        "UPDATE orders SET amount = 1, update_time = '2024-01-02 03:04:05.000000', update_id = 'u1', \
+         update_by = 'alice', version = version + 1 WHERE id = 1"
+    );
+
+    // 显式赋值的字段保持不变
+    let sql = rewrite(&hook, DatabaseBackend::MySql, "UPDATE orders SET amount = 1, update_by = 'bob' WHERE id = 1");
+    assert_eq!(
+        sql,
+        "UPDATE orders SET amount = 1, update_by = 'bob', update_time = '2024-01-02 03:04:05.000000', \
+         update_id = 'u1', version = version + 1 WHERE id = 1"
+    );
+
+    // 不包含版本号字段的表不递增版本号
+    let sql = rewrite(&hook, DatabaseBackend::MySql, "UPDATE users SET name = 'x' WHERE id = 1");
+    assert_eq!(
+        sql,
+        "UPDATE users SET name = 'x', update_time = '2024-01-02 03:04:05.000000', update_id = 'u1', \
+         update_by = 'alice' WHERE id = 1"
+    );
+}
+
+#[test]
+fn multi_table_update_fill_is_qualified() {
+    let hook = fill_hook();
+    let sql = rewrite(
+        &hook,
+        DatabaseBackend::MySql,
+        "UPDATE orders o JOIN users u ON u.id = o.user_id SET o.amount = 1",
+    );
+    assert_eq!(
+        sql,
+        "UPDATE orders o JOIN users u ON u.id = o.user_id SET o.amount = 1, o.update_time = '2024-01-02 03:04:05.000000', \
+         o.update_id = 'u1', o.update_by = 'alice', o.version = o.version + 1"
+    );
+
+    // PostgreSQL 的 SET 目标不带表名，取值中的版本号带表名避免与 FROM 中的表歧义
+    let sql = rewrite(
+        &hook,
+        DatabaseBackend::Postgres,
+        "UPDATE orders SET amount = 1 FROM users WHERE users.id = orders.user_id",
+    );
+    assert_eq!(
+        sql,
+        "UPDATE orders SET amount = 1, update_time = '2024-01-02 03:04:05.000000', update_id = 'u1', \
+         update_by = 'alice', version = orders.version + 1 FROM users WHERE users.id = orders.user_id"
+    );
+}
+
+#[test]
+fn update_fill_appends_bound_parameters() {
+    let hook = fill_hook();
+    let stmt = Statement::from_sql_and_values(
+        DatabaseBackend::MySql,
+        "UPDATE orders SET amount = ? WHERE id = ?",
+        [1.into(), 2.into()],
+    );
+    let rewritten = hook.before_statement(&stmt).unwrap().expect("statement was not rewritten");
+    assert_eq!(
+        rewritten.sql,
+        "UPDATE orders SET amount = ?, update_time = ?, update_id = ?, update_by = ?, version = version + 1 WHERE id = ?"
+    );
+    let values = rewritten.values.unwrap().0;
+    assert_eq!(values[0], Value::from(1));
+    assert_eq!(values[2..], [Value::from("u1"), Value::from("alice"), Value::from(2)]);
+}